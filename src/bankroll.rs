@@ -0,0 +1,57 @@
+/// A player's bankroll carried from round to round, along with the deepest
+/// drop it has taken from a previous high.
+#[derive(Debug, Clone, Copy)]
+pub struct Bankroll {
+    pub start: f64,
+    pub balance: f64,
+    pub peak: f64,
+    /// Largest drop from a peak, as a fraction of that peak.
+    pub max_drawdown: f64,
+    pub rounds: u64,
+    /// Total units put on the table.
+    pub wagered: f64,
+}
+
+impl Bankroll {
+    pub fn new(start: f64) -> Self {
+        Self {
+            start,
+            balance: start,
+            peak: start,
+            max_drawdown: 0.0,
+            rounds: 0,
+            wagered: 0.0,
+        }
+    }
+    /// Adds the result of a round to the bankroll.
+    pub fn settle(&mut self, wagered: f64, net: f64) {
+        self.wagered += wagered;
+        self.balance += net;
+        self.rounds += 1;
+        self.peak = self.peak.max(self.balance);
+        if self.peak > 0.0 {
+            self.max_drawdown = self.max_drawdown.max((self.peak - self.balance) / self.peak);
+        }
+    }
+    /// Whether the bankroll can no longer cover the smallest bet.
+    pub fn is_ruined(&self, min_bet: f64) -> bool {
+        self.balance < min_bet
+    }
+    /// Geometric growth of the bankroll per 100 rounds.
+    pub fn growth_per_100(&self) -> f64 {
+        if self.rounds == 0 || self.balance <= 0.0 {
+            return -1.0;
+        }
+        (self.balance / self.start).powf(100.0 / self.rounds as f64) - 1.0
+    }
+}
+
+/// Sorts values and returns the one at the given quantile.
+pub fn quantile(values: &mut [f64], q: f64) -> f64 {
+    if values.is_empty() {
+        return 0.0;
+    }
+    values.sort_by(|a, b| a.total_cmp(b));
+    let i = ((values.len() - 1) as f64 * q).round() as usize;
+    values[i]
+}
@@ -0,0 +1,200 @@
+use std::collections::BTreeMap;
+use std::str::FromStr;
+
+use crate::error::GameError;
+use crate::stats::Tally;
+use crate::GameResult;
+use crate::systems::{BettingSystem, System};
+
+/// A bet ramp sizes each wager off the true count before a round is dealt.
+/// All amounts are in betting units, where one unit is the flat bet that
+/// basic strategy alone would make.
+#[derive(Debug, Clone, Copy)]
+pub struct BetRamp {
+    /// Units added per point of true count.
+    pub units_per_true_count: f64,
+    /// Smallest and largest bet the player is willing to make.
+    pub min_units: f64,
+    pub max_units: f64,
+    /// Limits imposed by the table, applied after the player's own spread.
+    pub table_min: f64,
+    pub table_max: f64,
+}
+
+impl BetRamp {
+    /// Checks every amount is a finite number, the table limits are positive
+    /// with the minimum at most the maximum, and the player's spread the same
+    /// way round.
+    pub fn validate(&self) -> Result<(), GameError> {
+        let amounts = [
+            self.units_per_true_count,
+            self.min_units,
+            self.max_units,
+            self.table_min,
+            self.table_max,
+        ];
+        if !amounts.iter().all(|amount| amount.is_finite()) {
+            return Err(GameError::InvalidRules(format!(
+                "bets {}-{} at {} units per true count and table limits {}-{} must be finite",
+                self.min_units, self.max_units, self.units_per_true_count, self.table_min, self.table_max,
+            )));
+        }
+        if self.table_min <= 0.0 || self.table_min > self.table_max {
+            return Err(GameError::InvalidRules(format!(
+                "table limits {}-{} must be positive with the minimum at most the maximum",
+                self.table_min, self.table_max,
+            )));
+        }
+        if self.min_units > self.max_units {
+            return Err(GameError::InvalidRules(format!(
+                "minimum bet {} is above the maximum bet {}",
+                self.min_units, self.max_units,
+            )));
+        }
+        Ok(())
+    }
+    /// The wager for a round dealt at the given true count. True counts are
+    /// floored, as is customary when converting them to bets.
+    pub fn bet(&self, true_count: f64) -> f64 {
+        let units = true_count.floor() * self.units_per_true_count;
+        units
+            .clamp(self.min_units, self.max_units.max(self.min_units))
+            .clamp(self.table_min, self.table_max.max(self.table_min))
+    }
+}
+
+/// Back-counting: the player watches rounds without betting, enters the table
+/// once the true count reaches the entry threshold and leaves when it drops
+/// below the exit threshold or the shoe is shuffled.
+#[derive(Debug, Clone, Copy)]
+pub struct Wong {
+    pub entry: f64,
+    pub exit: f64,
+}
+
+impl Wong {
+    /// Whether the player is seated for the next round, given whether they
+    /// were seated for the last one.
+    pub fn seated(&self, was_seated: bool, true_count: f64) -> bool {
+        if was_seated {
+            true_count >= self.exit
+        } else {
+            true_count >= self.entry
+        }
+    }
+}
+
+/// Bets a fraction of the bankroll proportional to the player's estimated
+/// advantage at the current true count, which maximizes the long run growth
+/// of the bankroll at full Kelly. Fractional Kelly gives up some growth for
+/// much smaller swings.
+#[derive(Debug, Clone)]
+pub struct Kelly {
+    pub fraction: f64,
+    pub table_min: f64,
+    pub table_max: f64,
+    // Estimated advantage keyed by floored true count, and the variance of
+    // a hand, both in units.
+    advantages: BTreeMap<i32, f64>,
+    variance: f64,
+}
+
+// Buckets seen fewer times than this are too noisy to bet off.
+const MIN_KELLY_HANDS: u64 = 1000;
+
+impl Kelly {
+    /// Estimates the advantage at each true count from simulated flat bet
+    /// results, ignoring true counts that came up too rarely.
+    pub fn new(fraction: f64, table_min: f64, table_max: f64, by_true_count: &BTreeMap<i32, Tally>, all: &Tally) -> Self {
+        let advantages = by_true_count
+            .iter()
+            .filter(|(_, tally)| tally.hands >= MIN_KELLY_HANDS)
+            .map(|(tc, tally)| (*tc, tally.advantage()))
+            .collect();
+        Self {
+            fraction,
+            table_min,
+            table_max,
+            advantages,
+            variance: all.std_dev().powi(2).max(f64::EPSILON),
+        }
+    }
+    /// The estimated advantage at a true count. Counts beyond the range seen
+    /// often enough use the nearest estimate.
+    pub fn advantage(&self, true_count: f64) -> f64 {
+        let tc = true_count.floor() as i32;
+        if let Some(advantage) = self.advantages.get(&tc) {
+            return *advantage;
+        }
+        let below = self.advantages.range(..tc).next_back();
+        let above = self.advantages.range(tc..).next();
+        below.or(above).map_or(0.0, |(_, advantage)| *advantage)
+    }
+    /// The wager for a round, clamped to the table limits and the bankroll.
+    pub fn bet(&self, bankroll: f64, true_count: f64) -> f64 {
+        let optimal = self.fraction * self.advantage(true_count) / self.variance * bankroll;
+        optimal
+            .clamp(self.table_min, self.table_max.max(self.table_min))
+            .min(bankroll)
+    }
+}
+
+/// Kelly fractions can be given by name or as a number.
+pub fn parse_kelly_fraction(s: &str) -> Result<f64, String> {
+    match s {
+        "full" => Ok(1.0),
+        "half" => Ok(0.5),
+        "quarter" => Ok(0.25),
+        fraction => f64::from_str(fraction).map_err(|e| format!("bad Kelly fraction {}: {}", s, e)),
+    }
+}
+
+/// How the wager for each round of a session is chosen: off the count, or by
+/// a betting system that follows the results of previous rounds.
+#[derive(Debug, Clone)]
+pub enum BetSizing {
+    Ramp(BetRamp),
+    Kelly(Kelly),
+    Progression {
+        system: System,
+        table_min: f64,
+        table_max: f64,
+    },
+}
+
+impl BetSizing {
+    pub fn table_min(&self) -> f64 {
+        match self {
+            BetSizing::Ramp(ramp) => ramp.table_min,
+            BetSizing::Kelly(kelly) => kelly.table_min,
+            BetSizing::Progression { table_min, .. } => *table_min,
+        }
+    }
+    /// A fresh betting system for a session, if bets follow one.
+    pub fn progression(&self) -> Option<Box<dyn BettingSystem>> {
+        match self {
+            BetSizing::Progression { system, .. } => Some(system.start()),
+            _ => None,
+        }
+    }
+    /// The wager for a round, never more than the bankroll. Betting systems
+    /// bet in units of the table minimum and get the bet they ask for
+    /// within the table limits.
+    pub fn bet(
+        &self,
+        bankroll: f64,
+        true_count: f64,
+        progression: Option<(&mut dyn BettingSystem, Option<&GameResult>)>,
+    ) -> f64 {
+        let bet = match (self, progression) {
+            (BetSizing::Ramp(ramp), _) => ramp.bet(true_count),
+            (BetSizing::Kelly(kelly), _) => kelly.bet(bankroll, true_count),
+            (BetSizing::Progression { table_min, table_max, .. }, Some((system, previous))) => {
+                let units = system.next_bet(previous, *table_min);
+                (table_min * units).clamp(*table_min, table_max.max(*table_min))
+            }
+            (BetSizing::Progression { table_min, .. }, None) => *table_min,
+        };
+        bet.min(bankroll)
+    }
+}
@@ -0,0 +1,122 @@
+use std::fmt;
+use std::str::FromStr;
+
+use rand::seq::SliceRandom;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::composition::Composition;
+
+#[derive(Debug,PartialEq,Clone,Copy)]
+pub enum Card {
+    Two,
+    Three,
+    Four,
+    Five,
+    Six,
+    Seven,
+    Eight,
+    Nine,
+    Ten,
+    J,
+    Q,
+    K,
+    A,
+}
+
+/// Cards are written by their rank alone, e.g. A, 2, T or K.
+impl fmt::Display for Card {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Card::Two => "2",
+            Card::Three => "3",
+            Card::Four => "4",
+            Card::Five => "5",
+            Card::Six => "6",
+            Card::Seven => "7",
+            Card::Eight => "8",
+            Card::Nine => "9",
+            Card::Ten => "T",
+            Card::J => "J",
+            Card::Q => "Q",
+            Card::K => "K",
+            Card::A => "A",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl FromStr for Card {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_uppercase().as_str() {
+            "2" => Ok(Card::Two),
+            "3" => Ok(Card::Three),
+            "4" => Ok(Card::Four),
+            "5" => Ok(Card::Five),
+            "6" => Ok(Card::Six),
+            "7" => Ok(Card::Seven),
+            "8" => Ok(Card::Eight),
+            "9" => Ok(Card::Nine),
+            "T" | "10" => Ok(Card::Ten),
+            "J" => Ok(Card::J),
+            "Q" => Ok(Card::Q),
+            "K" => Ok(Card::K),
+            "A" => Ok(Card::A),
+            _ => Err(format!("unknown card {}", s)),
+        }
+    }
+}
+
+impl Serialize for Card {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Card {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+/// Turns a card into its u8 representation, as several face cards
+/// can all map to 10. For now, treats aces as mapping to 11.
+impl From<&Card> for u8 {
+    fn from(c: &Card) -> Self {
+        match c {
+            Card::Two => 2,
+            Card::Three => 3,
+            Card::Four => 4,
+            Card::Five => 5,
+            Card::Six => 6,
+            Card::Seven => 7,
+            Card::Eight => 8,
+            Card::Nine => 9,
+            Card::Ten => 10,
+            Card::J => 10,
+            Card::Q => 10,
+            Card::K => 10,
+            Card::A => 11,
+        }
+    }
+}
+
+/// The cards of a shoe, in the order they will be dealt once shuffled.
+#[derive(Debug, Clone)]
+pub struct Deck {
+    pub(crate) cards: Vec<Card>,
+}
+
+impl Deck {
+    /// Starts building a deck of standard 52 card decks, which cards can be
+    /// added to or taken out of before it is built.
+    pub fn builder(num_decks: usize) -> Composition {
+        Composition::standard(num_decks)
+    }
+    pub fn shuffle<R: Rng>(&mut self, rng: &mut R) {
+        self.cards.shuffle(rng);
+    }
+}
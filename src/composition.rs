@@ -0,0 +1,155 @@
+use std::fmt;
+use std::str::FromStr;
+
+use crate::count::{rank_index, CountingSystem, RANKS};
+use crate::{Card, Deck};
+
+/// Every kind of card in a deck, in the order decks are built.
+pub const CARD_KINDS: [Card; 13] = [
+    Card::A,
+    Card::Two,
+    Card::Three,
+    Card::Four,
+    Card::Five,
+    Card::Six,
+    Card::Seven,
+    Card::Eight,
+    Card::Nine,
+    Card::Ten,
+    Card::J,
+    Card::Q,
+    Card::K,
+];
+
+fn kind_index(card: &Card) -> usize {
+    CARD_KINDS.iter().position(|c| c == card).unwrap()
+}
+
+/// How many cards of each kind a shoe holds. Starts out as a number of
+/// standard 52 card decks, which cards can then be added to or taken out of.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Composition {
+    decks: usize,
+    counts: [usize; 13],
+}
+
+impl Composition {
+    pub fn standard(decks: usize) -> Self {
+        Self {
+            decks,
+            counts: [4 * decks; 13],
+        }
+    }
+    pub fn set(mut self, card: Card, count: usize) -> Self {
+        self.counts[kind_index(&card)] = count;
+        self
+    }
+    pub fn add(mut self, card: Card, count: usize) -> Self {
+        self.counts[kind_index(&card)] += count;
+        self
+    }
+    pub fn remove(mut self, card: Card, count: usize) -> Self {
+        let i = kind_index(&card);
+        self.counts[i] = self.counts[i].saturating_sub(count);
+        self
+    }
+    pub fn apply(self, changes: &Changes) -> Self {
+        changes.0.iter().fold(self, |composition, change| match *change {
+            Change::Set(card, count) => composition.set(card, count),
+            Change::Add(card, count) => composition.add(card, count),
+            Change::Remove(card, count) => composition.remove(card, count),
+        })
+    }
+    pub fn total(&self) -> usize {
+        self.counts.iter().sum()
+    }
+    /// How many cards of each counting rank the shoe holds, with tens, jacks,
+    /// queens and kings together.
+    pub fn rank_counts(&self) -> [usize; RANKS] {
+        let mut ranks = [0; RANKS];
+        for (card, count) in CARD_KINDS.iter().zip(self.counts.iter()) {
+            ranks[rank_index(card)] += count;
+        }
+        ranks
+    }
+    /// An unshuffled deck of the cards. A standard composition comes out in
+    /// the same order as a deck built suit by suit.
+    pub fn build(&self) -> Deck {
+        let most = self.counts.iter().copied().max().unwrap_or_default();
+        let mut cards = Vec::with_capacity(self.total());
+        for round in 0..most {
+            for (card, count) in CARD_KINDS.iter().zip(self.counts.iter()) {
+                if *count > round {
+                    cards.push(*card);
+                }
+            }
+        }
+        Deck { cards }
+    }
+    /// The running count a player who knows the composition starts a shoe
+    /// on, counting cards missing from the standard decks as if they had been
+    /// seen and extra cards as if they had been put back.
+    pub fn initial_running_count(&self, system: CountingSystem) -> i32 {
+        CARD_KINDS
+            .iter()
+            .zip(self.counts.iter())
+            .map(|(card, count)| ((4 * self.decks) as i32 - *count as i32) * system.tag(card))
+            .sum()
+    }
+}
+
+/// A change to a standard shoe: `5=0` leaves no fives, `T+8` adds eight tens,
+/// `A-2` takes out two aces and a card on its own, e.g. `K`, takes out one.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Change {
+    Set(Card, usize),
+    Add(Card, usize),
+    Remove(Card, usize),
+}
+
+impl FromStr for Change {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let count = |n: &str| {
+            n.parse::<usize>()
+                .map_err(|_| format!("bad card count in {}", s))
+        };
+        if let Some((card, n)) = s.split_once('=') {
+            return Ok(Change::Set(card.parse()?, count(n)?));
+        }
+        if let Some((card, n)) = s.split_once('+') {
+            return Ok(Change::Add(card.parse()?, count(n)?));
+        }
+        if let Some((card, n)) = s.split_once('-') {
+            return Ok(Change::Remove(card.parse()?, count(n)?));
+        }
+        Ok(Change::Remove(s.parse()?, 1))
+    }
+}
+
+/// A list of changes separated by spaces or commas, e.g. "5=0, T+16" or the
+/// cards already dealt from a shoe, "A 5 5 K".
+#[derive(Debug, Clone, PartialEq)]
+pub struct Changes(pub Vec<Change>);
+
+impl FromStr for Changes {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.split(|c: char| c.is_whitespace() || c == ',')
+            .filter(|change| !change.is_empty())
+            .map(str::parse)
+            .collect::<Result<Vec<Change>, String>>()
+            .map(Changes)
+    }
+}
+
+impl fmt::Display for Composition {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let counts: Vec<String> = CARD_KINDS
+            .iter()
+            .zip(self.counts.iter())
+            .map(|(card, count)| format!("{}:{}", card, count))
+            .collect();
+        write!(f, "{} cards ({})", self.total(), counts.join(" "))
+    }
+}
@@ -0,0 +1,207 @@
+use std::fmt;
+use std::str::FromStr;
+
+use crate::Card;
+
+/// Ranks as far as counting is concerned: two through nine, ten-valued cards
+/// and aces, indexed in that order.
+pub const RANKS: usize = 10;
+
+pub fn rank_index(card: &Card) -> usize {
+    match u8::from(card) {
+        11 => 9,
+        value => (value - 2) as usize,
+    }
+}
+
+/// Number of cards of a rank in a single 52 card deck.
+pub fn cards_per_deck(rank: usize) -> f64 {
+    if rank == 8 { 16.0 } else { 4.0 }
+}
+
+/// A card counting system assigns a tag to every card rank. Keeping a running
+/// sum of the tags of every card that has left the shoe tells the player
+/// whether the remaining cards favor them or the house.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum CountingSystem {
+    HiLo,
+    HiOptI,
+    HiOptII,
+    OmegaII,
+    ZenCount,
+    // Tags given explicitly for ranks two through ace.
+    Custom([i32; RANKS]),
+}
+
+impl CountingSystem {
+    /// Tags of ranks two through nine, ten-valued cards and aces.
+    pub fn tags(&self) -> [i32; RANKS] {
+        match self {
+            CountingSystem::HiLo => [1, 1, 1, 1, 1, 0, 0, 0, -1, -1],
+            CountingSystem::HiOptI => [0, 1, 1, 1, 1, 0, 0, 0, -1, 0],
+            CountingSystem::HiOptII => [1, 1, 2, 2, 1, 1, 0, 0, -2, 0],
+            CountingSystem::OmegaII => [1, 1, 2, 2, 2, 1, 0, -1, -2, 0],
+            CountingSystem::ZenCount => [1, 1, 2, 2, 2, 1, 0, 0, -2, -1],
+            CountingSystem::Custom(tags) => *tags,
+        }
+    }
+    /// The tag added to the running count when a card is seen.
+    pub fn tag(&self, card: &Card) -> i32 {
+        self.tags()[rank_index(card)]
+    }
+    /// The largest tag of any rank, e.g. 2 for Hi-Opt II and Omega II.
+    pub fn level(&self) -> i32 {
+        self.tags().iter().map(|t| t.abs()).max().unwrap_or(0)
+    }
+}
+
+/// Systems are given by name, or as ten comma separated tags for ranks two
+/// through ace, e.g. "1,1,1,1,1,0,0,0,-1,-1" for Hi-Lo.
+impl FromStr for CountingSystem {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "hi-lo" | "hilo" => Ok(CountingSystem::HiLo),
+            "hi-opt-i" | "hiopt1" => Ok(CountingSystem::HiOptI),
+            "hi-opt-ii" | "hiopt2" => Ok(CountingSystem::HiOptII),
+            "omega-ii" | "omega2" => Ok(CountingSystem::OmegaII),
+            "zen" => Ok(CountingSystem::ZenCount),
+            tags if tags.contains(',') => {
+                let tags = tags
+                    .split(',')
+                    .map(|t| t.trim().parse::<i32>().map_err(|e| format!("bad tag {}: {}", t, e)))
+                    .collect::<Result<Vec<i32>, String>>()?;
+                let tags: [i32; RANKS] = tags
+                    .try_into()
+                    .map_err(|_| format!("expected {} tags in {}", RANKS, s))?;
+                Ok(CountingSystem::Custom(tags))
+            }
+            _ => Err(format!("unknown counting system {}", s)),
+        }
+    }
+}
+
+impl fmt::Display for CountingSystem {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            CountingSystem::HiLo => "Hi-Lo",
+            CountingSystem::HiOptI => "Hi-Opt I",
+            CountingSystem::HiOptII => "Hi-Opt II",
+            CountingSystem::OmegaII => "Omega II",
+            CountingSystem::ZenCount => "Zen Count",
+            CountingSystem::Custom(tags) => return write!(f, "Custom {:?}", tags),
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Tracks the running count of a shoe along with how many of its cards
+/// have been seen, so the running count can be converted to a true count.
+#[derive(Debug, Clone)]
+pub struct Counter {
+    system: CountingSystem,
+    running_count: i32,
+    // Running count at the start of a shoe, for shoes that aren't made of
+    // standard decks.
+    initial_count: i32,
+    cards_seen: usize,
+    total_cards: usize,
+    // Cards of each rank the shoe starts with, which for standard decks is
+    // the usual share of the total.
+    starting_by_rank: [f64; RANKS],
+    seen_by_rank: [u32; RANKS],
+    // Points added to the running count per surplus ace left in the shoe,
+    // for systems that keep a separate count of aces.
+    ace_side_count: Option<i32>,
+}
+
+impl Counter {
+    pub fn new(system: CountingSystem, total_cards: usize) -> Self {
+        Self {
+            system,
+            running_count: 0,
+            initial_count: 0,
+            cards_seen: 0,
+            total_cards,
+            starting_by_rank: std::array::from_fn(|rank| cards_per_deck(rank) * total_cards as f64 / 52.0),
+            seen_by_rank: [0; RANKS],
+            ace_side_count: None,
+        }
+    }
+    pub fn with_initial_count(mut self, count: i32) -> Self {
+        self.initial_count = count;
+        self.running_count = count;
+        self
+    }
+    /// Counts cards of each rank against what the shoe really starts with,
+    /// for shoes that aren't made of standard decks.
+    pub fn with_starting_counts(mut self, counts: [usize; RANKS]) -> Self {
+        self.starting_by_rank = counts.map(|count| count as f64);
+        self
+    }
+    /// Keeps a side count of aces. Ace neutral systems like Hi-Opt II and
+    /// Omega II leave aces out of the running count because they behave like
+    /// small cards when playing hands, but aces favor the player when betting.
+    pub fn with_ace_side_count(mut self, points_per_ace: i32) -> Self {
+        self.ace_side_count = Some(points_per_ace);
+        self
+    }
+    /// How many more aces are left in the shoe than standard decks would
+    /// hold among as many cards.
+    pub fn surplus_aces(&self) -> f64 {
+        -self.deficit(9)
+    }
+    // How many fewer cards of a rank are left in the shoe than standard
+    // decks would hold among as many cards: how many more have been seen
+    // than an even draw would remove, plus how many the shoe was short of
+    // to begin with.
+    fn deficit(&self, rank: usize) -> f64 {
+        let expected = self.cards_seen as f64 * cards_per_deck(rank) / 52.0;
+        let missing = self.total_cards as f64 * cards_per_deck(rank) / 52.0 - self.starting_by_rank[rank];
+        self.seen_by_rank[rank] as f64 - expected + missing
+    }
+    pub fn observe(&mut self, card: &Card) {
+        self.running_count += self.system.tag(card);
+        self.cards_seen += 1;
+        self.seen_by_rank[rank_index(card)] += 1;
+    }
+    pub fn running_count(&self) -> i32 {
+        self.running_count
+    }
+    pub fn reset(&mut self) {
+        self.running_count = self.initial_count;
+        self.cards_seen = 0;
+        self.seen_by_rank = [0; RANKS];
+    }
+    /// Number of decks left in the shoe. Never drops below a quarter deck so
+    /// the true count stays bounded at the very end of a shoe.
+    pub fn decks_remaining(&self) -> f64 {
+        let remaining = self.total_cards.saturating_sub(self.cards_seen) as f64 / 52.0;
+        remaining.max(0.25)
+    }
+    /// The running count normalized by the number of decks left to be dealt.
+    /// With an ace side count, the running count is first adjusted for the
+    /// aces left in the shoe, which is what bets and playing deviations use.
+    pub fn true_count(&self) -> f64 {
+        let adjustment = self
+            .ace_side_count
+            .map_or(0.0, |points| points as f64 * self.surplus_aces());
+        (self.running_count as f64 + adjustment) / self.decks_remaining()
+    }
+    /// The true count used for insurance. Aces are not tens, so a surplus of
+    /// aces makes insurance worse rather than better and the side count
+    /// adjustment goes the other way.
+    pub fn insurance_true_count(&self) -> f64 {
+        let adjustment = self
+            .ace_side_count
+            .map_or(0.0, |points| points as f64 * self.surplus_aces());
+        (self.running_count as f64 - adjustment) / self.decks_remaining()
+    }
+    /// How many fewer cards of each rank are left than standard decks would
+    /// hold, per deck remaining, which for a shoe of standard decks is how
+    /// many more have been seen than an even draw would have removed. This
+    /// is the shoe composition the effects of removal of each rank apply to.
+    pub fn excess_removed(&self) -> [f64; RANKS] {
+        std::array::from_fn(|rank| self.deficit(rank) / self.decks_remaining())
+    }
+}
@@ -0,0 +1,176 @@
+use std::fs;
+use std::str::FromStr;
+
+use crate::Move;
+
+/// What a deviation changes: either the move made in a strategy situation or
+/// whether to take insurance against a dealer ace.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Play {
+    Move(Move),
+    Insurance,
+}
+
+/// An index play overrides basic strategy in a situation once the true count
+/// reaches (or, for negative deviations, drops below) a threshold. Situations
+/// use the same keys as the basic strategy table, e.g. "16,10" for hard 16
+/// against a dealer ten or "10,10,5" for a pair of tens against a five.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Deviation {
+    pub situation: String,
+    pub index: f64,
+    /// Whether the play applies at or above the index rather than below it.
+    pub at_or_above: bool,
+    pub play: Play,
+}
+
+impl Deviation {
+    fn new(situation: &str, at_or_above: bool, index: f64, play: Play) -> Self {
+        Self {
+            situation: situation.to_string(),
+            index,
+            at_or_above,
+            play,
+        }
+    }
+    pub fn applies(&self, true_count: f64) -> bool {
+        if self.at_or_above {
+            true_count >= self.index
+        } else {
+            true_count < self.index
+        }
+    }
+}
+
+/// Parses a single line of a deviations file, of the form
+/// `<situation> <>=|<> <index> <move>`, e.g. `16,10 >= 0 Stand` or
+/// `insurance >= 3`.
+impl FromStr for Deviation {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parts: Vec<&str> = s.split_whitespace().collect();
+        let at_or_above = match parts.get(1) {
+            Some(&">=") => true,
+            Some(&"<") => false,
+            _ => return Err(format!("expected >= or < in deviation {}", s)),
+        };
+        let index = parts
+            .get(2)
+            .and_then(|i| i.parse::<f64>().ok())
+            .ok_or_else(|| format!("missing index in deviation {}", s))?;
+        let situation = parts[0];
+        let play = if situation == "insurance" {
+            Play::Insurance
+        } else {
+            let action = parts
+                .get(3)
+                .ok_or_else(|| format!("missing move in deviation {}", s))?;
+            Play::Move(action.parse()?)
+        };
+        Ok(Self::new(situation, at_or_above, index, play))
+    }
+}
+
+/// A set of index plays consulted before basic strategy.
+#[derive(Debug, Clone, Default)]
+pub struct Deviations {
+    pub plays: Vec<Deviation>,
+}
+
+impl Deviations {
+    /// The Hi-Lo indices that account for most of the gain from playing
+    /// deviations, from Don Schlesinger's Blackjack Attack.
+    pub fn illustrious_18() -> Self {
+        let plays = vec![
+            Deviation::new("insurance", true, 3.0, Play::Insurance),
+            Deviation::new("16,10", true, 0.0, Play::Move(Move::Stand)),
+            Deviation::new("15,10", true, 4.0, Play::Move(Move::Stand)),
+            Deviation::new("10,10,5", true, 5.0, Play::Move(Move::Split)),
+            Deviation::new("10,10,6", true, 4.0, Play::Move(Move::Split)),
+            Deviation::new("10,10", true, 4.0, Play::Move(Move::Double)),
+            Deviation::new("12,3", true, 2.0, Play::Move(Move::Stand)),
+            Deviation::new("12,2", true, 3.0, Play::Move(Move::Stand)),
+            Deviation::new("11,11", true, 1.0, Play::Move(Move::Double)),
+            Deviation::new("9,2", true, 1.0, Play::Move(Move::Double)),
+            Deviation::new("10,11", true, 4.0, Play::Move(Move::Double)),
+            Deviation::new("9,7", true, 3.0, Play::Move(Move::Double)),
+            Deviation::new("16,9", true, 5.0, Play::Move(Move::Stand)),
+            Deviation::new("13,2", false, -1.0, Play::Move(Move::Hit)),
+            Deviation::new("12,4", false, 0.0, Play::Move(Move::Hit)),
+            Deviation::new("12,5", false, -2.0, Play::Move(Move::Hit)),
+            Deviation::new("12,6", false, -1.0, Play::Move(Move::Hit)),
+            Deviation::new("13,3", false, -2.0, Play::Move(Move::Hit)),
+        ];
+        Self { plays }
+    }
+    /// The four Hi-Lo surrender indices worth knowing.
+    pub fn fab_4() -> Self {
+        let plays = vec![
+            Deviation::new("14,10", true, 3.0, Play::Move(Move::Surrender)),
+            Deviation::new("15,10", true, 0.0, Play::Move(Move::Surrender)),
+            Deviation::new("15,9", true, 2.0, Play::Move(Move::Surrender)),
+            Deviation::new("15,11", true, 1.0, Play::Move(Move::Surrender)),
+        ];
+        Self { plays }
+    }
+    /// Loads deviations from a file with one deviation per line. Blank lines
+    /// and lines starting with # are ignored.
+    pub fn from_file(path: &str) -> eyre::Result<Self> {
+        let contents = fs::read_to_string(path)?;
+        let mut plays = vec![];
+        for line in contents.lines().map(str::trim) {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            plays.push(line.parse::<Deviation>().map_err(|e| eyre::eyre!(e))?);
+        }
+        Ok(Self { plays })
+    }
+    /// Whether there are no index plays, leaving basic strategy alone.
+    pub fn is_empty(&self) -> bool {
+        self.plays.is_empty()
+    }
+    /// A set containing only the deviation at the given position.
+    pub fn only(&self, i: usize) -> Self {
+        Self {
+            plays: self.plays.get(i).cloned().into_iter().collect(),
+        }
+    }
+    /// The move to make in a situation at the given true count, if any
+    /// deviation applies. The first matching deviation wins, so surrender
+    /// indices listed before other plays take precedence.
+    pub fn get(&self, situation: &str, true_count: f64, can_surrender: bool) -> Option<(usize, Move)> {
+        self.plays.iter().enumerate().find_map(|(i, d)| match &d.play {
+            Play::Move(Move::Surrender) if !can_surrender => None,
+            Play::Move(action) if d.situation == situation && d.applies(true_count) => {
+                Some((i, action.clone()))
+            }
+            _ => None,
+        })
+    }
+    /// Whether to take insurance at the given true count, and which deviation
+    /// said to.
+    pub fn insurance(&self, true_count: f64) -> Option<usize> {
+        self.plays
+            .iter()
+            .position(|d| d.play == Play::Insurance && d.applies(true_count))
+    }
+}
+
+/// Deviations can be selected by name or loaded from a file.
+impl FromStr for Deviations {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "none" => Ok(Self::default()),
+            "i18" => Ok(Self::illustrious_18()),
+            "fab4" => Ok(Self::fab_4()),
+            "i18+fab4" => {
+                let mut deviations = Self::fab_4();
+                deviations.plays.extend(Self::illustrious_18().plays);
+                Ok(deviations)
+            }
+            path => Self::from_file(path).map_err(|e| format!("{}: {}", path, e)),
+        }
+    }
+}
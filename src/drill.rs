@@ -0,0 +1,135 @@
+use std::io::{self, Write};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use blackjack_rs::shoe::CardSource;
+use blackjack_rs::GameError;
+
+use crate::play::ask;
+use crate::Opt;
+
+// Cards dealt between checkpoints, chosen at random in this range so the
+// player can't anticipate them.
+const MIN_CARDS_BETWEEN_CHECKS: usize = 5;
+const MAX_CARDS_BETWEEN_CHECKS: usize = 20;
+
+// A true count within this much of the exact one is accepted, which allows
+// for rounding the decks remaining to the nearest half deck.
+const TRUE_COUNT_TOLERANCE: f64 = 0.5;
+
+#[derive(Debug, Default)]
+struct DrillStats {
+    cards: usize,
+    checks: usize,
+    running_correct: usize,
+    true_correct: usize,
+    // Time spent answering the running count, which is how long the player
+    // needs to keep up.
+    answer_time: Duration,
+}
+
+enum Answer {
+    Number(f64),
+    Quit,
+}
+
+// Asks for a number until one is given, or the player quits.
+fn ask_number(question: &str) -> Answer {
+    loop {
+        match ask(question).as_deref() {
+            None | Some("q") => return Answer::Quit,
+            Some(answer) => match answer.parse() {
+                Ok(number) => return Answer::Number(number),
+                Err(_) => println!("Enter a number, or q to quit."),
+            },
+        }
+    }
+}
+
+// Deals cards from the shoe one at a time, `speed` cards a second, and stops
+// at random checkpoints to ask for the running and true count of the
+// configured counting system. Runs until the player quits.
+pub fn drill(opts: &Opt, seed: u64, speed: f64) -> eyre::Result<()> {
+    if speed <= 0.0 {
+        return Err(eyre::eyre!("the drill speed must be above zero, got {}", speed));
+    }
+    let delay = Duration::from_secs_f64(1.0 / speed);
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut shoe = opts.shoe(seed)?;
+    let mut stats = DrillStats::default();
+    let started = Instant::now();
+    println!(
+        "Counting drill with {} from {} decks at {} cards a second. Enter q to quit.",
+        opts.counting_system, opts.num_decks, speed,
+    );
+    'drill: loop {
+        let cards = rng.gen_range(MIN_CARDS_BETWEEN_CHECKS..=MAX_CARDS_BETWEEN_CHECKS);
+        for _ in 0..cards {
+            if shoe.shuffle_if_needed() {
+                println!("\rShuffle. The count starts over at {}.", shoe.counter().running_count());
+            }
+            let card = shoe.next().ok_or(GameError::ExhaustedShoe)?;
+            stats.cards += 1;
+            // Each card replaces the last one so earlier cards can't be
+            // looked back at.
+            print!("\r{}", card);
+            io::stdout().flush()?;
+            thread::sleep(delay);
+        }
+        println!("\r ");
+        let asked = Instant::now();
+        let running_count = match ask_number("Running count? ") {
+            Answer::Number(count) => count,
+            Answer::Quit => break 'drill,
+        };
+        stats.answer_time += asked.elapsed();
+        let true_count = match ask_number("True count? ") {
+            Answer::Number(count) => count,
+            Answer::Quit => break 'drill,
+        };
+        stats.checks += 1;
+        let exact_running = shoe.counter().running_count();
+        let exact_true = shoe.true_count();
+        if running_count == exact_running as f64 {
+            stats.running_correct += 1;
+            print!("Running count right");
+        } else {
+            print!("Running count was {}", exact_running);
+        }
+        if (true_count - exact_true).abs() <= TRUE_COUNT_TOLERANCE {
+            stats.true_correct += 1;
+            println!(", true count right ({:.1}).", exact_true);
+        } else {
+            println!(
+                ", true count was {:.1} with {:.1} decks left.",
+                exact_true,
+                shoe.counter().decks_remaining(),
+            );
+        }
+    }
+    let elapsed = started.elapsed().as_secs_f64();
+    println!();
+    println!("Cards dealt: {} in {:.0}s at {} cards a second", stats.cards, elapsed, speed);
+    if stats.checks > 0 {
+        println!(
+            "Running count: {} of {} right ({:.0}%)",
+            stats.running_correct,
+            stats.checks,
+            stats.running_correct as f64 / stats.checks as f64 * 100.0,
+        );
+        println!(
+            "True count: {} of {} right ({:.0}%)",
+            stats.true_correct,
+            stats.checks,
+            stats.true_correct as f64 / stats.checks as f64 * 100.0,
+        );
+        println!(
+            "Average time to give the running count: {:.1}s",
+            stats.answer_time.as_secs_f64() / stats.checks as f64,
+        );
+    }
+    Ok(())
+}
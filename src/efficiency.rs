@@ -0,0 +1,237 @@
+use std::collections::BTreeMap;
+use std::sync::{Arc, Mutex};
+
+use crate::count::{cards_per_deck, RANKS};
+use crate::deviations::{Deviations, Play};
+use crate::ror;
+use crate::shoe::{CardSource, Shoe};
+use crate::simulation::Simulation;
+use crate::stats::Tally;
+use crate::strategy::BASIC_STRATEGY;
+use crate::{Game, GameError, GameResult, Move};
+
+/// Single deck effects of removal on the player's expectation, in percent, for
+/// ranks two through nine, tens and aces (Griffin, The Theory of Blackjack).
+pub const BETTING_EOR: [f64; RANKS] = [0.38, 0.44, 0.55, 0.69, 0.46, 0.28, 0.00, -0.18, -0.51, -0.61];
+
+/// Single deck effects of removal on the insurance bet, in percent. Only
+/// whether a card is a ten matters.
+pub const INSURANCE_EOR: [f64; RANKS] = [1.81, 1.81, 1.81, 1.81, 1.81, 1.81, 1.81, 1.81, -4.07, 1.81];
+
+/// Pearson correlation between a tag vector and a vector of effects of removal,
+/// taken over all 52 cards of a deck so ten-valued cards count four times.
+pub fn correlation(tags: &[i32; RANKS], eors: &[f64; RANKS]) -> f64 {
+    let weight = |rank: usize| cards_per_deck(rank) / 52.0;
+    let mean_t: f64 = (0..RANKS).map(|r| weight(r) * tags[r] as f64).sum();
+    let mean_e: f64 = (0..RANKS).map(|r| weight(r) * eors[r]).sum();
+    let mut covariance = 0.0;
+    let mut var_t = 0.0;
+    let mut var_e = 0.0;
+    for rank in 0..RANKS {
+        let t = tags[rank] as f64 - mean_t;
+        let e = eors[rank] - mean_e;
+        covariance += weight(rank) * t * e;
+        var_t += weight(rank) * t * t;
+        var_e += weight(rank) * e * e;
+    }
+    if var_t == 0.0 || var_e == 0.0 {
+        return 0.0;
+    }
+    covariance / (var_t * var_e).sqrt()
+}
+
+/// SCORE: the expected win per 100 hands, in dollars, of a player betting a
+/// $10,000 bankroll optimally with the given results per hand.
+pub fn score(tally: &Tally) -> f64 {
+    let sd = tally.std_dev();
+    if sd == 0.0 {
+        return 0.0;
+    }
+    1_000_000.0 * (tally.mean() / sd).powi(2)
+}
+
+/// N0: the number of hands it takes for the expected win to equal one standard
+/// deviation of the results.
+pub fn n0(tally: &Tally) -> f64 {
+    ror::n0(tally.mean(), tally.std_dev())
+}
+
+/// Desirability index: the square root of SCORE, or 1000 times the ratio of
+/// win rate to standard deviation.
+pub fn desirability_index(tally: &Tally) -> f64 {
+    let sd = tally.std_dev();
+    if sd == 0.0 {
+        return 0.0;
+    }
+    1000.0 * tally.mean() / sd
+}
+
+// Number of coefficients fit: an intercept plus all ranks but aces. Since the
+// excess of every rank removed always sums to zero, one rank has to be left
+// out and its effect recovered afterwards.
+const COEFFICIENTS: usize = RANKS;
+
+/// Least squares regression of a round's outcome on the composition of the
+/// cards removed from the shoe, whose coefficients are the effects of removal.
+#[derive(Debug, Clone, Default)]
+pub struct Regression {
+    xtx: [[f64; COEFFICIENTS]; COEFFICIENTS],
+    xty: [f64; COEFFICIENTS],
+    pub samples: u64,
+}
+
+impl Regression {
+    pub fn record(&mut self, excess_removed: &[f64; RANKS], y: f64) {
+        let mut x = [1.0; COEFFICIENTS];
+        x[1..].copy_from_slice(&excess_removed[..RANKS - 1]);
+        for i in 0..COEFFICIENTS {
+            for j in 0..COEFFICIENTS {
+                self.xtx[i][j] += x[i] * x[j];
+            }
+            self.xty[i] += x[i] * y;
+        }
+        self.samples += 1;
+    }
+    pub fn merge(&mut self, other: &Regression) {
+        for i in 0..COEFFICIENTS {
+            for j in 0..COEFFICIENTS {
+                self.xtx[i][j] += other.xtx[i][j];
+            }
+            self.xty[i] += other.xty[i];
+        }
+        self.samples += other.samples;
+    }
+    /// The effect of removal of each rank in percent, shifted so that removing
+    /// a whole deck's worth of cards has no effect.
+    pub fn eors(&self) -> Option<[f64; RANKS]> {
+        let beta = solve(self.xtx, self.xty)?;
+        let mut eors = [0.0; RANKS];
+        eors[..RANKS - 1].copy_from_slice(&beta[1..]);
+        let mean: f64 = (0..RANKS).map(|r| eors[r] * cards_per_deck(r) / 52.0).sum();
+        for e in eors.iter_mut() {
+            *e = (*e - mean) * 100.0;
+        }
+        Some(eors)
+    }
+}
+
+// Solves a linear system by Gaussian elimination with partial pivoting.
+fn solve(
+    mut a: [[f64; COEFFICIENTS]; COEFFICIENTS],
+    mut b: [f64; COEFFICIENTS],
+) -> Option<[f64; COEFFICIENTS]> {
+    for col in 0..COEFFICIENTS {
+        let pivot = (col..COEFFICIENTS).max_by(|i, j| a[*i][col].abs().total_cmp(&a[*j][col].abs()))?;
+        if a[pivot][col].abs() < 1e-12 {
+            return None;
+        }
+        a.swap(col, pivot);
+        b.swap(col, pivot);
+        let pivot_row = a[col];
+        for row in col + 1..COEFFICIENTS {
+            let factor = a[row][col] / pivot_row[col];
+            for (k, value) in a[row].iter_mut().enumerate().skip(col) {
+                *value -= factor * pivot_row[k];
+            }
+            b[row] -= factor * b[col];
+        }
+    }
+    let mut x = [0.0; COEFFICIENTS];
+    for row in (0..COEFFICIENTS).rev() {
+        let sum: f64 = (row + 1..COEFFICIENTS).map(|k| a[row][k] * x[k]).sum();
+        x[row] = (b[row] - sum) / a[row][row];
+    }
+    Some(x)
+}
+
+/// Effects of removal estimated from simulated rounds: on the outcome of the
+/// round for betting, and on the difference between the index play and the
+/// basic strategy move for each playing decision.
+#[derive(Debug, Default)]
+pub struct EorEstimator {
+    pub betting: Regression,
+    pub playing: BTreeMap<String, Regression>,
+}
+
+impl EorEstimator {
+    /// Fits the regressions to the simulation's rounds, split across
+    /// threads, for the situations the given index plays cover.
+    pub fn simulate(simulation: &Simulation, deviations: &Deviations) -> Result<Self, GameError> {
+        let deviations = deviations.clone();
+        simulation.run_merged(
+            move |estimator: &mut Self, shoe, rounds| estimator.play_shoe(shoe, rounds, &deviations),
+            Self::merge,
+        )
+    }
+    /// Plays rounds from a shoe. Whenever the first decision of a round is one
+    /// of the given index plays, the round is also played out with the index
+    /// play and with basic strategy from copies of the shoe.
+    pub fn play_shoe(&mut self, shoe: Shoe, rounds: usize, deviations: &Deviations) -> Result<(), GameError> {
+        let strat = BASIC_STRATEGY.lock().unwrap().clone();
+        let mut cards = Arc::new(Mutex::new(shoe));
+        for _ in 0..rounds {
+            let (before, excess_removed) = {
+                let mut shoe = cards.lock().unwrap();
+                shoe.shuffle_if_needed();
+                (shoe.clone(), shoe.counter().excess_removed())
+            };
+            let mut game = Game::new(&mut cards)?;
+            if !game.game_ended().0 {
+                let situation = game.situation();
+                let alternative = deviations.plays.iter().find_map(|d| match &d.play {
+                    Play::Move(action) if d.situation == situation => Some(action.clone()),
+                    _ => None,
+                });
+                if let (Some(alternative), Some(basic)) = (alternative, strat.get(situation.as_str())) {
+                    // The composition the decision is made on: the cards
+                    // gone before the round, the player's two cards and the
+                    // up card, but not the hole card the player can't see.
+                    let mut seen = before.counter().clone();
+                    for card in game.player_hands().0.iter().chain(&game.dealer_hand()[..1]) {
+                        seen.observe(card);
+                    }
+                    let excess_removed = seen.excess_removed();
+                    let units = |action: Move| -> Result<f64, GameError> {
+                        let mut replay_cards = Arc::new(Mutex::new(before.clone()));
+                        let mut replay = Game::new(&mut replay_cards)?.with_first_move(action);
+                        replay.start()?;
+                        Ok(GameResult::from(replay).units())
+                    };
+                    let gain = units(alternative)? - units(basic.clone())?;
+                    self.playing
+                        .entry(situation)
+                        .or_default()
+                        .record(&excess_removed, gain);
+                }
+            }
+            game.start()?;
+            self.betting
+                .record(&excess_removed, GameResult::from(game).units());
+        }
+        Ok(())
+    }
+    pub fn merge(&mut self, other: EorEstimator) {
+        self.betting.merge(&other.betting);
+        for (situation, regression) in other.playing {
+            self.playing.entry(situation).or_default().merge(&regression);
+        }
+    }
+    /// Playing efficiency: the correlation between the tags and the effects of
+    /// removal of each playing decision, averaged over decisions weighted by
+    /// how often they came up. Negative index plays correlate negatively, so
+    /// the strength of the correlation is what counts.
+    pub fn playing_efficiency(&self, tags: &[i32; RANKS]) -> Option<f64> {
+        let mut total = 0.0;
+        let mut weights = 0.0;
+        for regression in self.playing.values() {
+            if let Some(eors) = regression.eors() {
+                total += regression.samples as f64 * correlation(tags, &eors).abs();
+                weights += regression.samples as f64;
+            }
+        }
+        if weights == 0.0 {
+            return None;
+        }
+        Some(total / weights)
+    }
+}
@@ -0,0 +1,119 @@
+use std::fs;
+use std::sync::{Arc, Mutex};
+
+use rand::seq::IteratorRandom;
+
+use crate::count::{rank_index, CountingSystem, RANKS};
+use crate::shoe::Shoe;
+use crate::simulation::Simulation;
+use crate::stats::Tally;
+use crate::{Card, Game, GameError, GameResult};
+
+/// Names of the ranks in the order effects of removal are reported in.
+pub const RANK_NAMES: [&str; RANKS] = ["2", "3", "4", "5", "6", "7", "8", "9", "T", "A"];
+
+/// Estimates the effect on the player's expectation of removing a single card
+/// of each rank from a full shoe, by simulation.
+///
+/// Every round is dealt off the top of a freshly shuffled shoe, and then off
+/// the same shuffle with a card of each rank, picked at random, taken out.
+/// Dropping a uniformly picked card of a rank from a uniformly shuffled shoe
+/// leaves a uniformly shuffled shoe of the remaining cards, so each estimate
+/// is unbiased. The card dropped is usually far from the top, so most rounds
+/// are dealt the exact same cards either way and the differences have little
+/// noise. Always dropping the first card of a rank instead would push the
+/// rest of the rank down the shoe, away from the round.
+#[derive(Debug, Default, Clone)]
+pub struct EorCalculator {
+    pub full: Tally,
+    differences: [Tally; RANKS],
+}
+
+impl EorCalculator {
+    /// Estimates the effects of removal over the simulation's rounds, split
+    /// across threads.
+    pub fn simulate(simulation: &Simulation) -> Result<Self, GameError> {
+        simulation.run_merged(
+            |calculator: &mut Self, shoe, rounds| calculator.play_shoe(shoe, rounds),
+            |calculator, other| calculator.merge(&other),
+        )
+    }
+    pub fn play_shoe(&mut self, mut shoe: Shoe, rounds: usize) -> Result<(), GameError> {
+        for _ in 0..rounds {
+            shoe.shuffle();
+            let cards = shoe.remaining().to_vec();
+            let full = play_round(cards.clone())?;
+            self.full.record(1.0, full);
+            for (rank, difference) in self.differences.iter_mut().enumerate() {
+                let mut removed = cards.clone();
+                let picked = (0..removed.len())
+                    .filter(|i| rank_index(&removed[*i]) == rank)
+                    .choose(shoe.rng());
+                if let Some(i) = picked {
+                    removed.remove(i);
+                }
+                difference.record(1.0, play_round(removed)? - full);
+            }
+        }
+        Ok(())
+    }
+    pub fn merge(&mut self, other: &EorCalculator) {
+        self.full.merge(&other.full);
+        for (ours, theirs) in self.differences.iter_mut().zip(other.differences.iter()) {
+            ours.merge(theirs);
+        }
+    }
+    /// Change in the player's expectation, in percent, from removing one card
+    /// of each rank.
+    pub fn eors(&self) -> [f64; RANKS] {
+        let mut eors = [0.0; RANKS];
+        for (e, difference) in eors.iter_mut().zip(self.differences.iter()) {
+            *e = difference.mean() * 100.0;
+        }
+        eors
+    }
+    /// Standard errors of the effects of removal, in percent.
+    pub fn std_errors(&self) -> [f64; RANKS] {
+        let mut errors = [0.0; RANKS];
+        for (e, difference) in errors.iter_mut().zip(self.differences.iter()) {
+            *e = difference.std_dev() / (difference.hands.max(1) as f64).sqrt() * 100.0;
+        }
+        errors
+    }
+}
+
+// Plays a single round off the top of the given cards with basic strategy.
+fn play_round(cards: Vec<Card>) -> Result<f64, GameError> {
+    let mut cards = Arc::new(Mutex::new(Shoe::stacked(cards, CountingSystem::HiLo)));
+    let mut game = Game::new(&mut cards)?;
+    game.start()?;
+    Ok(GameResult::from(game).units())
+}
+
+/// Effects of removal in a shoe of N cards are roughly 51 / (N - 1) times the
+/// single deck ones, so scaling them back makes shoes of different sizes
+/// comparable with each other and with published single deck tables.
+pub fn single_deck_equivalent(eors: &[f64; RANKS], total_cards: usize) -> [f64; RANKS] {
+    let scale = (total_cards as f64 - 1.0) / 51.0;
+    let mut scaled = *eors;
+    for e in scaled.iter_mut() {
+        *e *= scale;
+    }
+    scaled
+}
+
+/// Writes the table of effects of removal as CSV.
+pub fn write_csv(path: &str, calculator: &EorCalculator, total_cards: usize) -> eyre::Result<()> {
+    let eors = calculator.eors();
+    let errors = calculator.std_errors();
+    let scaled = single_deck_equivalent(&eors, total_cards);
+    let mut contents = String::from("rank,eor_percent,std_error_percent,single_deck_eor_percent\n");
+    for rank in 0..RANKS {
+        contents.push_str(&format!(
+            "{},{:.5},{:.5},{:.5}\n",
+            RANK_NAMES[rank], eors[rank], errors[rank], scaled[rank],
+        ));
+    }
+    fs::write(path, contents)?;
+    Ok(())
+}
@@ -0,0 +1,25 @@
+use thiserror::Error;
+
+use crate::game::{Move, Phase};
+
+/// Why a round couldn't be dealt or played on.
+#[derive(Debug, Clone, PartialEq, Error)]
+pub enum GameError {
+    /// A move that isn't one of the legal moves at this point of the round.
+    #[error("{action:?} isn't a legal move in phase {phase:?}")]
+    IllegalMove { action: Move, phase: Phase },
+    /// Insurance was answered while the round wasn't offering it.
+    #[error("insurance isn't offered in phase {0:?}")]
+    InsuranceNotOffered(Phase),
+    /// A stacked shoe ran out of cards in the middle of a round. Other shoes
+    /// are reshuffled instead.
+    #[error("the shoe ran out of cards")]
+    ExhaustedShoe,
+    /// Neither the strategy table nor an index play covers the situation.
+    #[error("no strategy entry for situation {0}")]
+    MissingStrategy(String),
+    /// A shoe or bet ramp that can't be played with, reported by
+    /// `Shoe::new` and `BetRamp::validate`.
+    #[error("invalid rules: {0}")]
+    InvalidRules(String),
+}
@@ -0,0 +1,796 @@
+use std::fmt;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+
+use serde::{Deserialize, Serialize};
+
+use crate::cards::Card;
+use crate::count;
+use crate::deviations::Deviations;
+use crate::error::GameError;
+use crate::hand::{hand_sum, take_two};
+use crate::rules::RULES;
+use crate::shoe::CardSource;
+use crate::situations;
+use crate::strategy::BASIC_STRATEGY;
+
+/// A valid move a player can make in the game.
+#[derive(Debug,PartialEq,Eq,Hash,Clone,Serialize,Deserialize)]
+pub enum Move {
+    Double,
+    Stand,
+    Hit,
+    Split,
+    Surrender,
+}
+
+impl FromStr for Move {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "double" => Ok(Move::Double),
+            "stand" => Ok(Move::Stand),
+            "hit" => Ok(Move::Hit),
+            "split" => Ok(Move::Split),
+            "surrender" => Ok(Move::Surrender),
+            _ => Err(format!("unknown move {}", s)),
+        }
+    }
+}
+
+/// A move the player made and the situation and true count it was made at.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Decision {
+    pub situation: String,
+    pub true_count: f64,
+    pub action: Move,
+}
+
+/// The kinds of players in the game.
+#[derive(Debug,PartialEq,Copy,Clone,Serialize,Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Agent {
+    Dealer,
+    Player,
+}
+
+/// Decides the player's moves in place of the strategy, e.g. a person
+/// playing at the terminal.
+pub trait Player: fmt::Debug {
+    /// Picks one of the legal moves for the hand against the dealer's up card.
+    fn decide(&mut self, hand: &[Card], dealer_up_card: Card, legal: &[Move]) -> Move;
+    /// Whether to take insurance against the dealer's ace.
+    fn insure(&mut self, hand: &[Card]) -> bool;
+}
+
+/// Where a round is. Every round goes from dealing, through insurance when
+/// the dealer shows an ace, to the player's turn and then the dealer's, and
+/// ends settled, though it can be settled at any point along the way.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Phase {
+    /// The initial cards are dealt but nothing has been checked yet.
+    Dealing,
+    /// The dealer shows an ace and the player can take insurance.
+    Insurance,
+    /// The player decides on a hand: the first, and once a pair is split,
+    /// the second after it.
+    PlayerTurn(usize),
+    /// The dealer turns over the hole card and draws.
+    DealerTurn,
+    /// The round is over and `winner` holds the result.
+    Settled,
+}
+
+/// A round of blackjack. A round can be played out with [`Game::start`], or
+/// driven a step at a time by checking its [`Phase`] and making the
+/// decisions it waits on with [`Game::insure`] and [`Game::apply`], leaving
+/// the rest to [`Game::step`].
+#[derive(Debug)]
+pub struct Game<'a, T: Iterator> {
+    pub(crate) deck: &'a mut Arc<Mutex<T>>,
+    pub(crate) dealer_hand: Vec<Card>,
+    pub(crate) dealer_total: u8,
+    pub(crate) player_moves: Vec<Move>,
+    pub(crate) player_hands: (Vec<Card>, Vec<Card>),
+    pub(crate) player_total: u8,
+    /// Every card dealt in the round, in order, and the decisions made.
+    pub(crate) dealt: Vec<Card>,
+    pub(crate) decisions: Vec<Decision>,
+    pub(crate) dealer_beats_player: bool,
+    pub(crate) winner: Option<Agent>,
+    /// Who won each hand once a pair is split, which decides the winner, and
+    /// which hands were doubled.
+    pub(crate) hand_winners: [Option<Agent>; 2],
+    pub(crate) doubled: [bool; 2],
+    /// Index plays consulted before basic strategy, and the ones used.
+    pub(crate) deviations: Option<&'a Deviations>,
+    pub(crate) deviations_used: Vec<usize>,
+    pub(crate) insured: bool,
+    /// A move the player makes first regardless of strategy.
+    pub(crate) first_move: Option<Move>,
+    /// Makes every decision instead of the strategy when set.
+    pub(crate) player: Option<&'a mut dyn Player>,
+    pub(crate) phase: Phase,
+    pub(crate) dealer_revealed: bool,
+    /// How many initial bets the player's chips cover, which limits the
+    /// doubles, splits and insurance they can afford.
+    pub(crate) bets_covered: f64,
+    /// Whether the counter has seen the hole card, which happens when the
+    /// dealer turns it over or, if the dealer never plays, as the round is
+    /// settled.
+    pub(crate) hole_card_counted: bool,
+}
+
+impl <'a, T> Game<'a, T> where T: CardSource {
+    pub fn new(
+        cards: &'a mut Arc<Mutex<T>>,
+    ) -> Result<Self, GameError> {
+        // The dealer's second card is the hole card, dealt face down so the
+        // count doesn't include it until it is turned over.
+        let dealer_hand = {
+            let mut deck = cards.lock().unwrap();
+            let up_card = deck.next().ok_or(GameError::ExhaustedShoe)?;
+            let hole_card = deck.deal_face_down().ok_or(GameError::ExhaustedShoe)?;
+            vec![up_card, hole_card]
+        };
+        let player_hand = take_two(cards)?;
+        let dealer_total = u8::from(&dealer_hand[0]);
+        let player_total = hand_sum(&player_hand);
+        let dealt = [dealer_hand.as_slice(), player_hand.as_slice()].concat();
+        Ok(Self {
+            deck: cards,
+            dealer_hand,
+            dealer_total,
+            player_hands: (player_hand, vec![]),
+            player_total,
+            player_moves: vec![],
+            dealt,
+            decisions: vec![],
+            dealer_beats_player: false,
+            winner: None,
+            hand_winners: [None; 2],
+            doubled: [false; 2],
+            deviations: None,
+            deviations_used: vec![],
+            insured: false,
+            first_move: None,
+            player: None,
+            phase: Phase::Dealing,
+            dealer_revealed: false,
+            bets_covered: f64::INFINITY,
+            hole_card_counted: false,
+        })
+    }
+    pub fn with_deviations(mut self, deviations: &'a Deviations) -> Self {
+        self.deviations = Some(deviations);
+        self
+    }
+    /// Forces the player's first decision, after which strategy takes over.
+    pub fn with_first_move(mut self, action: Move) -> Self {
+        self.first_move = Some(action);
+        self
+    }
+    /// Limits the player to the chips they have behind a bet, so that a
+    /// double or split the chips can't cover is never made and insurance
+    /// is only taken when they can pay for it. A double the strategy calls
+    /// for is hit instead, and an unaffordable pair is played by its total.
+    pub fn with_chips(mut self, chips: f64, bet: f64) -> Self {
+        self.bets_covered = chips / bet;
+        self
+    }
+    /// Lets a player make the decisions instead of the strategy.
+    pub fn with_player(mut self, player: &'a mut dyn Player) -> Self {
+        self.player = Some(player);
+        self
+    }
+    pub fn dealer_hand(&self) -> &[Card] {
+        &self.dealer_hand
+    }
+    pub fn dealer_total(&self) -> u8 {
+        self.dealer_total
+    }
+    /// The player's hand and, once a pair is split, the second hand.
+    pub fn player_hands(&self) -> (&[Card], &[Card]) {
+        (&self.player_hands.0, &self.player_hands.1)
+    }
+    /// The total of the hand being played, which is the second hand once the
+    /// first of a split pair is done.
+    pub fn player_total(&self) -> u8 {
+        self.player_total
+    }
+    pub fn player_moves(&self) -> &[Move] {
+        &self.player_moves
+    }
+    /// Who won the round, or None for a push or a round not yet played.
+    /// After a split, the player wins when the hands won more units than they lost.
+    pub fn winner(&self) -> Option<Agent> {
+        self.winner
+    }
+    /// Who won each hand of a split round. Both are None until the round is
+    /// settled or if no pair was split.
+    pub fn hand_winners(&self) -> [Option<Agent>; 2] {
+        self.hand_winners
+    }
+    pub fn phase(&self) -> Phase {
+        self.phase
+    }
+    /// Plays the round out, with the strategy, deviations or player making
+    /// every decision.
+    pub fn start(&mut self) -> Result<(), GameError> {
+        while self.step()? != Phase::Settled {}
+        Ok(())
+    }
+    /// Moves the round on by one step, making whatever decision it waits on
+    /// the way `start` would, and returns the phase it is in after.
+    pub fn step(&mut self) -> Result<Phase, GameError> {
+        match self.phase {
+            Phase::Dealing => {
+                if self.dealer_hand.first() == Some(&Card::A) {
+                    self.phase = Phase::Insurance;
+                } else {
+                    self.phase = Phase::PlayerTurn(0);
+                    self.settle_if_ended();
+                }
+            }
+            Phase::Insurance => {
+                self.insured = self.take_insurance();
+                self.phase = Phase::PlayerTurn(0);
+                self.settle_if_ended();
+            }
+            Phase::PlayerTurn(_) => {
+                let action = self.act()?;
+                self.perform(action)?;
+            }
+            Phase::DealerTurn => self.dealer_step()?,
+            Phase::Settled => {}
+        }
+        Ok(self.phase)
+    }
+    /// Steps through the round until it waits on the player, to insure or
+    /// make a move, or is settled, and returns that phase.
+    pub fn step_until_decision(&mut self) -> Result<Phase, GameError> {
+        while matches!(self.phase, Phase::Dealing | Phase::DealerTurn) {
+            self.step()?;
+        }
+        Ok(self.phase)
+    }
+    /// Takes or declines insurance when the dealer shows an ace.
+    pub fn insure(&mut self, take: bool) -> Result<Phase, GameError> {
+        if self.phase == Phase::Dealing {
+            self.step()?;
+        }
+        if self.phase != Phase::Insurance {
+            return Err(GameError::InsuranceNotOffered(self.phase));
+        }
+        self.insured = take;
+        self.phase = Phase::PlayerTurn(0);
+        self.settle_if_ended();
+        Ok(self.phase)
+    }
+    /// Makes a move on the player's hand. The move must be one of
+    /// `legal_moves`.
+    pub fn apply(&mut self, action: Move) -> Result<Phase, GameError> {
+        if self.phase == Phase::Dealing {
+            self.step()?;
+        }
+        if !self.legal_moves().contains(&action) {
+            return Err(GameError::IllegalMove { action, phase: self.phase });
+        }
+        self.perform(action)?;
+        Ok(self.phase)
+    }
+    // Makes a move without checking it is legal, as strategy tables and
+    // forced first moves are trusted to.
+    fn perform(&mut self, action: Move) -> Result<(), GameError> {
+        self.decisions.push(Decision {
+            situation: self.situation(),
+            true_count: self.true_count(),
+            action: action.clone(),
+        });
+        let hand = self.hand();
+        match action {
+            Move::Hit | Move::Double => {
+                let card = self.next_card()?;
+                self.hand_mut().push(card);
+                self.player_total += u8::from(&card);
+                if action == Move::Double {
+                    self.doubled[hand] = true;
+                }
+            },
+            Move::Split => {
+                // Pop a card from the hand and push it to the second hand.
+                let splitter = self.player_hands.0.pop().unwrap();
+                self.player_hands.1.push(splitter);
+
+                // Hit both hands.
+                let card = self.next_card()?;
+                self.player_hands.0.push(card);
+                let card = self.next_card()?;
+                self.player_hands.1.push(card);
+
+                // Each hand is played and settled on its own, the first
+                // hand first.
+                self.player_total = hand_sum(&self.player_hands.0);
+            },
+            Move::Stand => {},
+            Move::Surrender => {
+                // Give up half the bet and end the round.
+                self.player_moves.push(action);
+                self.winner = Some(Agent::Dealer);
+                self.phase = Phase::Settled;
+                self.count_hole_card();
+                return Ok(());
+            },
+        }
+        if action == Move::Split {
+            if self.player_total >= 21 {
+                self.next_hand();
+            }
+        } else if self.is_split() {
+            if action != Move::Hit || self.player_total >= 21 {
+                self.next_hand();
+            }
+        } else if action != Move::Hit {
+            self.phase = Phase::DealerTurn;
+        }
+        self.player_moves.push(action);
+        self.settle_if_ended();
+        Ok(())
+    }
+    // The hand the player is deciding on.
+    fn hand(&self) -> usize {
+        match self.phase {
+            Phase::PlayerTurn(hand) => hand,
+            _ => 0,
+        }
+    }
+    fn hand_mut(&mut self) -> &mut Vec<Card> {
+        match self.hand() {
+            0 => &mut self.player_hands.0,
+            _ => &mut self.player_hands.1,
+        }
+    }
+    pub(crate) fn current_hand(&self) -> &[Card] {
+        match self.hand() {
+            0 => &self.player_hands.0,
+            _ => &self.player_hands.1,
+        }
+    }
+    // Moves on from a split hand that stood, doubled, busted or reached 21,
+    // to the second hand or else the dealer. A second hand of 21 has nothing
+    // to decide.
+    fn next_hand(&mut self) {
+        let hand = self.hand();
+        self.phase = Phase::DealerTurn;
+        if hand == 0 {
+            self.player_total = hand_sum(&self.player_hands.1);
+            if self.player_total < 21 {
+                self.phase = Phase::PlayerTurn(1);
+            }
+        }
+    }
+    // Turns over the hole card, or draws a card once it is showing.
+    fn dealer_step(&mut self) -> Result<(), GameError> {
+        if !self.dealer_revealed {
+            self.dealer_total += u8::from(self.dealer_hand.last().unwrap());
+            self.dealer_revealed = true;
+            self.count_hole_card();
+        } else {
+            let card = self.next_card()?;
+            self.dealer_hand.push(card);
+            self.dealer_total += u8::from(&card);
+
+            // Split rounds are settled hand by hand in split_outcome.
+            if self.dealer_total <= 21 && self.dealer_total > self.player_total {
+                self.dealer_beats_player = true;
+            }
+        }
+        self.settle_if_ended();
+        Ok(())
+    }
+    fn settle_if_ended(&mut self) {
+        if self.is_split() {
+            if let Some(hand_winners) = self.split_outcome() {
+                self.hand_winners = hand_winners;
+                self.winner = split_winner(&hand_winners, &self.doubled);
+                self.phase = Phase::Settled;
+                self.count_hole_card();
+            }
+            return;
+        }
+        if let (true, winner) = self.game_ended() {
+            self.winner = winner;
+            self.phase = Phase::Settled;
+            self.count_hole_card();
+        }
+    }
+    fn is_split(&self) -> bool {
+        !self.player_hands.1.is_empty()
+    }
+    // Who won each hand of a split round, once it is over. The dealer turns
+    // over the hole card and draws until busting or reaching the best total
+    // among the hands that haven't busted, and each hand is then compared
+    // with the dealer's. If both hands bust the dealer doesn't play.
+    fn split_outcome(&self) -> Option<[Option<Agent>; 2]> {
+        let totals = [hand_sum(&self.player_hands.0), hand_sum(&self.player_hands.1)];
+        let best = totals.iter().copied().filter(|t| *t <= 21).max();
+        if let Some(best) = best {
+            let dealer_done = self.dealer_total > 21 || self.dealer_total >= best;
+            if !self.dealer_revealed || !dealer_done {
+                return None;
+            }
+        }
+        Some(totals.map(|total| {
+            if total > 21 {
+                Some(Agent::Dealer)
+            } else if self.dealer_total > 21 || total > self.dealer_total {
+                Some(Agent::Player)
+            } else if total < self.dealer_total {
+                Some(Agent::Dealer)
+            } else {
+                None
+            }
+        }))
+    }
+    // Lets the counter see the hole card, once it is turned over.
+    fn count_hole_card(&mut self) {
+        if !self.hole_card_counted {
+            self.deck.lock().unwrap().reveal(&self.dealer_hand[1]);
+            self.hole_card_counted = true;
+        }
+    }
+    pub(crate) fn next_card(&mut self) -> Result<Card, GameError> {
+        let card = self.deck
+            .lock()
+            .unwrap()
+            .next()
+            .ok_or(GameError::ExhaustedShoe)?;
+        self.dealt.push(card);
+        Ok(card)
+    }
+    pub fn true_count(&self) -> f64 {
+        self.deck.lock().unwrap().true_count()
+    }
+    pub fn excess_removed(&self) -> [f64; count::RANKS] {
+        self.deck.lock().unwrap().counter().excess_removed()
+    }
+    /// Basic strategy never takes insurance, but a deviation may at a high
+    /// enough count when the dealer shows an ace.
+    pub(crate) fn take_insurance(&mut self) -> bool {
+        if self.dealer_hand.first() != Some(&Card::A) || self.bets_covered < 1.5 {
+            return false;
+        }
+        if let Some(player) = &mut self.player {
+            return player.insure(&self.player_hands.0);
+        }
+        let true_count = self.deck.lock().unwrap().counter().insurance_true_count();
+        match self.deviations.and_then(|d| d.insurance(true_count)) {
+            Some(i) => {
+                self.deviations_used.push(i);
+                true
+            }
+            None => false,
+        }
+    }
+    // Looks up an index play for the situation at the current true count.
+    // Surrender is only possible as the first decision on the initial hand.
+    fn deviation(&mut self, key: &str) -> Option<Move> {
+        let deviations = self.deviations?;
+        let true_count = self.true_count();
+        let can_surrender = self.player_moves.is_empty()
+            && self.player_hands.0.len() == 2
+            && !self.is_split();
+        let (i, action) = deviations.get(key, true_count, can_surrender)?;
+        self.deviations_used.push(i);
+        Some(action)
+    }
+    /// The basic strategy key for the hand being played: the pair and dealer
+    /// up card for an initial pair, otherwise the hand total and dealer up
+    /// card.
+    pub fn situation(&self) -> String {
+        let dealer_up_card = u8::from(self.dealer_hand.first().unwrap());
+        if self.has_pair() {
+            let first = u8::from(self.player_hands.0.first().unwrap());
+            return format!("{},{},{}", first, first, dealer_up_card);
+        }
+        format!("{},{}", hand_sum(self.current_hand()), dealer_up_card)
+    }
+    /// The moves allowed now, which are none outside the player's turn.
+    /// Doubling, splitting a pair and surrendering are only allowed as the
+    /// first decision on the two card hand. Each hand of a split pair may
+    /// be doubled on its first two cards, but not split again or
+    /// surrendered.
+    pub fn legal_moves(&self) -> Vec<Move> {
+        if !matches!(self.phase, Phase::PlayerTurn(_)) {
+            return vec![];
+        }
+        let mut moves = vec![Move::Hit, Move::Stand];
+        if self.is_split() {
+            if self.current_hand().len() == 2 && self.can_afford_another_bet() {
+                moves.push(Move::Double);
+            }
+        } else if self.player_moves.is_empty() && self.player_hands.0.len() == 2 {
+            if self.can_afford_another_bet() {
+                moves.push(Move::Double);
+                if self.has_pair() {
+                    moves.push(Move::Split);
+                }
+            }
+            moves.push(Move::Surrender);
+        }
+        moves
+    }
+    // Whether the chips cover another bet on top of those already out and
+    // any insurance, as a double or split puts out.
+    fn can_afford_another_bet(&self) -> bool {
+        let hands = if self.is_split() { 2.0 } else { 1.0 };
+        let doubles = self.doubled.iter().filter(|d| **d).count() as f64;
+        let insurance = if self.insured { 0.5 } else { 0.0 };
+        self.bets_covered >= hands + doubles + insurance + 1.0
+    }
+    /// Do cards match? If so, then the pair strategy applies. A split pair
+    /// isn't split again.
+    pub fn has_pair(&self) -> bool {
+        !self.is_split()
+            && self.player_hands.0.len() == 2
+            && self.player_hands.0[0] == self.player_hands.0[1]
+    }
+    /// The player's next move: a forced first move, the player's choice,
+    /// which must be legal, or else an index play or basic strategy.
+    pub(crate) fn act(&mut self) -> Result<Move, GameError> {
+        if let Some(action) = self.first_move.take() {
+            return Ok(action);
+        }
+        if self.player.is_some() {
+            let legal = self.legal_moves();
+            let dealer_up_card = self.dealer_hand[0];
+            if let Some(player) = &mut self.player {
+                let hand = match self.phase {
+                    Phase::PlayerTurn(1) => &self.player_hands.1,
+                    _ => &self.player_hands.0,
+                };
+                let action = player.decide(hand, dealer_up_card, &legal);
+                if !legal.contains(&action) {
+                    return Err(GameError::IllegalMove { action, phase: self.phase });
+                }
+                return Ok(action);
+            }
+        }
+        let key = self.situation();
+        let action = match self.deviation(&key) {
+            Some(action) => action,
+            None => self.strategy_move().ok_or_else(|| GameError::MissingStrategy(key.clone()))?,
+        };
+        // A split hand can only be doubled on its first two cards.
+        let split_hit = self.is_split() && self.current_hand().len() > 2;
+        if self.can_afford_another_bet() && !(split_hit && action == Move::Double) {
+            return Ok(action);
+        }
+        match action {
+            Move::Double => Ok(Move::Hit),
+            Move::Split => match self.total_move() {
+                Some(Move::Double) => Ok(Move::Hit),
+                Some(action) => Ok(action),
+                None => Err(GameError::MissingStrategy(key)),
+            },
+            action => Ok(action),
+        }
+    }
+    /// The basic strategy move for the player's hand, if there is one.
+    pub fn strategy_move(&self) -> Option<Move> {
+        if self.has_pair() {
+            let strat = BASIC_STRATEGY.lock().unwrap();
+            return strat.get(self.situation().as_str()).cloned();
+        }
+        self.total_move()
+    }
+    // The basic strategy move for the player's hand total, which is how a
+    // pair is played when it isn't split.
+    fn total_move(&self) -> Option<Move> {
+        let player_sum = hand_sum(self.current_hand());
+        let dealer_up_card = u8::from(self.dealer_hand.first().unwrap());
+        let key = format!("{},{}", player_sum, dealer_up_card);
+        let strat = BASIC_STRATEGY.lock().unwrap();
+
+        // Always hit if < 5.
+        if player_sum < 5 {
+            return Some(Move::Hit);
+        }
+
+        // Always stand if sum > 17.
+        if player_sum > 17 {
+            return Some(Move::Stand);
+        }
+
+        strat.get(key.as_str()).cloned()
+    }
+    pub(crate) fn game_ended(&self) -> (bool, Option<Agent>) {
+        // A split round is settled hand by hand.
+        if self.is_split() {
+            return match self.split_outcome() {
+                Some(hand_winners) => (true, split_winner(&hand_winners, &self.doubled)),
+                None => (false, None),
+            };
+        }
+        if self.player_total == self.dealer_total {
+            return (true, None);
+        }
+        if self.dealer_beats_player {
+            return (true, Some(Agent::Dealer)); 
+        }
+        if self.player_total == 21 {
+            return (true, Some(Agent::Player));
+        }
+        if self.dealer_total == 21 {
+            return (true, Some(Agent::Dealer));
+        }
+        if self.player_total > 21 {
+            return (true, Some(Agent::Dealer)); 
+        }
+        if self.dealer_total > 21 {
+            return (true, Some(Agent::Player)); 
+        }
+        (false, None)
+    }
+}
+
+// Units won or lost on a hand of a split round, which is two if it was
+// doubled.
+fn hand_units(winner: &Option<Agent>, doubled: bool) -> f64 {
+    let units = if doubled { 2.0 } else { 1.0 };
+    match winner {
+        Some(Agent::Player) => units,
+        Some(Agent::Dealer) => -units,
+        None => 0.0,
+    }
+}
+
+// Units won or lost on both hands of a split round.
+fn split_units(hand_winners: &[Option<Agent>; 2], doubled: &[bool; 2]) -> f64 {
+    hand_winners.iter().zip(doubled).map(|(w, d)| hand_units(w, *d)).sum()
+}
+
+// The winner of a split round is whoever came out ahead over the hands.
+fn split_winner(hand_winners: &[Option<Agent>; 2], doubled: &[bool; 2]) -> Option<Agent> {
+    let net = split_units(hand_winners, doubled);
+    if net > 0.0 {
+        Some(Agent::Player)
+    } else if net < 0.0 {
+        Some(Agent::Dealer)
+    } else {
+        None
+    }
+}
+
+/// Simple summary of the game for displaying to the user.
+pub struct GameResult {
+    pub(crate) dealer_hand: Vec<Card>,
+    pub(crate) player_hands: (Vec<Card>, Vec<Card>),
+    pub(crate) player_moves: Vec<Move>,
+    pub(crate) dealt: Vec<Card>,
+    pub(crate) decisions: Vec<Decision>,
+    pub(crate) winner: Option<Agent>,
+    pub(crate) hand_winners: [Option<Agent>; 2],
+    pub(crate) doubled: [bool; 2],
+    /// Units bet on the round and the true count it was dealt at.
+    pub(crate) bet: f64,
+    pub(crate) true_count: f64,
+    pub(crate) insured: bool,
+    pub(crate) dealer_blackjack: bool,
+    pub(crate) deviations_used: Vec<usize>,
+}
+
+impl GameResult {
+    pub fn with_bet(mut self, bet: f64, true_count: f64) -> Self {
+        self.bet = bet;
+        self.true_count = true_count;
+        self
+    }
+    pub fn dealer_hand(&self) -> &[Card] {
+        &self.dealer_hand
+    }
+    pub fn player_hands(&self) -> (&[Card], &[Card]) {
+        (&self.player_hands.0, &self.player_hands.1)
+    }
+    pub fn player_moves(&self) -> &[Move] {
+        &self.player_moves
+    }
+    /// Every card dealt in the round, in order.
+    pub fn dealt(&self) -> &[Card] {
+        &self.dealt
+    }
+    pub fn decisions(&self) -> &[Decision] {
+        &self.decisions
+    }
+    pub fn winner(&self) -> Option<Agent> {
+        self.winner
+    }
+    /// Who won each hand of a split round, both None if no pair was split.
+    pub fn hand_winners(&self) -> [Option<Agent>; 2] {
+        self.hand_winners
+    }
+    pub fn insured(&self) -> bool {
+        self.insured
+    }
+    pub fn bet(&self) -> f64 {
+        self.bet
+    }
+    pub fn true_count(&self) -> f64 {
+        self.true_count
+    }
+    /// Number of initial bets put on the table, counting doubles and splits.
+    pub fn units_wagered(&self) -> f64 {
+        if !self.player_hands.1.is_empty() {
+            return 2.0 + self.doubled.iter().filter(|d| **d).count() as f64;
+        }
+        let doubled = self.player_moves.contains(&Move::Double);
+        if doubled { 2.0 } else { 1.0 }
+    }
+    /// Whether the player was dealt a natural 21, which pays 3 to 2.
+    pub fn is_blackjack(&self) -> bool {
+        self.player_moves.is_empty()
+            && self.player_hands.0.len() == 2
+            && hand_sum(&self.player_hands.0) == 21
+    }
+    /// Units won or lost per unit of the initial bet, including insurance,
+    /// which costs half a unit and pays 2 to 1 against a dealer blackjack.
+    pub fn units(&self) -> f64 {
+        let insurance = match (self.insured, self.dealer_blackjack) {
+            (false, _) => 0.0,
+            (true, true) => 0.5 * RULES.insurance_payout,
+            (true, false) => -0.5,
+        };
+        if !self.player_hands.1.is_empty() {
+            return insurance + split_units(&self.hand_winners, &self.doubled);
+        }
+        let surrendered = self.player_moves.last() == Some(&Move::Surrender);
+        insurance + match self.winner {
+            Some(Agent::Dealer) if surrendered => -0.5,
+            Some(Agent::Player) if self.is_blackjack() => RULES.blackjack_payout,
+            Some(Agent::Player) => self.units_wagered(),
+            Some(Agent::Dealer) => -self.units_wagered(),
+            None => 0.0,
+        }
+    }
+    /// Units won or lost on the round at the bet made.
+    pub fn net(&self) -> f64 {
+        self.units() * self.bet
+    }
+    /// The two cards the player was dealt. A split moves the second card to
+    /// the second hand.
+    pub fn starting_hand(&self) -> situations::StartingHand {
+        match self.player_hands.1.first() {
+            Some(second) => situations::StartingHand::new(&[self.player_hands.0[0], *second]),
+            None => situations::StartingHand::new(&self.player_hands.0[..2]),
+        }
+    }
+    pub fn dealer_up_card(&self) -> u8 {
+        u8::from(&self.dealer_hand[0])
+    }
+    /// Whether any index play was used instead of basic strategy.
+    pub fn deviated(&self) -> bool {
+        !self.deviations_used.is_empty()
+    }
+}
+
+impl <'a, T> From<Game<'a, T>> for GameResult
+    where T: CardSource {
+    fn from(g: Game<'a, T>) -> Self {
+        let dealer_blackjack = hand_sum(&g.dealer_hand[..2]) == 21;
+        Self {
+            insured: g.insured,
+            dealer_blackjack,
+            deviations_used: g.deviations_used,
+            dealer_hand: g.dealer_hand,
+            player_hands: g.player_hands,
+            player_moves: g.player_moves,
+            dealt: g.dealt,
+            decisions: g.decisions,
+            winner: g.winner,
+            hand_winners: g.hand_winners,
+            doubled: g.doubled,
+            bet: 1.0,
+            true_count: 0.0,
+        } 
+    }
+}
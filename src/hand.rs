@@ -0,0 +1,18 @@
+use std::sync::{Arc, Mutex};
+
+use crate::cards::Card;
+use crate::error::GameError;
+
+/// Get the sum of cards in hand.
+pub fn hand_sum(hand: &[Card]) -> u8 {
+    hand.iter().map(u8::from).sum()
+}
+
+/// Take two cards from the deck iterator.
+pub(crate) fn take_two<T: Iterator<Item=Card>>(cards: &mut Arc<Mutex<T>>) -> Result<Vec<Card>, GameError> {
+    let binding = cards.clone();
+    let mut deck = binding.lock().unwrap();
+    let first = deck.next().ok_or(GameError::ExhaustedShoe)?;
+    let second = deck.next().ok_or(GameError::ExhaustedShoe)?;
+    Ok(vec![first, second])
+}
@@ -0,0 +1,220 @@
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::sync::{Arc, Mutex};
+
+use serde::{Deserialize, Serialize};
+
+use crate::deviations::Deviations;
+use crate::shoe::{Shoe, ShoeConfig};
+use crate::{Agent, Card, Decision, Game, GameError, GameResult, Move};
+
+/// Where a round was dealt from: the seed of the shoe, how many times it had
+/// been reshuffled and how many cards had been dealt since.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct ShoePosition {
+    pub seed: u64,
+    pub shuffle: u64,
+    pub position: usize,
+}
+
+impl From<&Shoe> for ShoePosition {
+    fn from(shoe: &Shoe) -> Self {
+        Self {
+            seed: shoe.seed(),
+            shuffle: shoe.shuffles(),
+            position: shoe.position(),
+        }
+    }
+}
+
+/// A single round of a hand history, written as one line of JSON.
+#[derive(Debug, Serialize)]
+pub struct HandRecord<'a> {
+    pub shoe: ShoePosition,
+    pub true_count: f64,
+    pub bet: f64,
+    /// Every card dealt in the round in order: the dealer's two cards, the
+    /// player's two cards and then each card drawn.
+    pub cards: &'a [Card],
+    pub dealer_hand: &'a [Card],
+    pub player_hands: Vec<&'a [Card]>,
+    pub insured: bool,
+    pub decisions: &'a [Decision],
+    pub winner: Option<Agent>,
+    /// Units won per unit bet, and units won at the bet made.
+    pub units: f64,
+    pub net: f64,
+}
+
+impl<'a> HandRecord<'a> {
+    pub fn new(shoe: ShoePosition, result: &'a GameResult) -> Self {
+        let mut player_hands = vec![result.player_hands.0.as_slice()];
+        if !result.player_hands.1.is_empty() {
+            player_hands.push(result.player_hands.1.as_slice());
+        }
+        Self {
+            shoe,
+            true_count: result.true_count,
+            bet: result.bet,
+            cards: &result.dealt,
+            dealer_hand: &result.dealer_hand,
+            player_hands,
+            insured: result.insured,
+            decisions: &result.decisions,
+            winner: result.winner,
+            units: result.units(),
+            net: result.net(),
+        }
+    }
+}
+
+/// Writes hand records as newline delimited JSON. Clones share the same file,
+/// so every simulation thread can write to it; records from different
+/// threads are told apart by the seed of their shoe. The first error hit is
+/// kept and returned by `finish`.
+#[derive(Debug, Clone)]
+pub struct HistoryWriter {
+    out: Arc<Mutex<(BufWriter<File>, Option<io::Error>)>>,
+}
+
+impl HistoryWriter {
+    pub fn create(path: &str) -> eyre::Result<Self> {
+        let file = File::create(path)?;
+        Ok(Self {
+            out: Arc::new(Mutex::new((BufWriter::new(file), None))),
+        })
+    }
+    pub fn write(&self, record: &HandRecord) {
+        let mut out = self.out.lock().unwrap();
+        if out.1.is_some() {
+            return;
+        }
+        let written = serde_json::to_writer(&mut out.0, record)
+            .map_err(io::Error::from)
+            .and_then(|_| out.0.write_all(b"\n"));
+        if let Err(e) = written {
+            out.1 = Some(e);
+        }
+    }
+    pub fn finish(&self) -> eyre::Result<()> {
+        let mut out = self.out.lock().unwrap();
+        if let Some(e) = out.1.take() {
+            return Err(e.into());
+        }
+        out.0.flush()?;
+        Ok(())
+    }
+}
+
+/// A round read back from a hand history, with the fields needed to replay it.
+#[derive(Debug, Deserialize)]
+pub struct RecordedHand {
+    pub shoe: ShoePosition,
+    pub true_count: f64,
+    pub bet: f64,
+    pub cards: Vec<Card>,
+    pub decisions: Vec<Decision>,
+    pub winner: Option<Agent>,
+    pub units: f64,
+}
+
+/// Reads a hand history written by `HistoryWriter`.
+pub fn read_history(path: &str) -> eyre::Result<Vec<RecordedHand>> {
+    let contents = fs::read_to_string(path)?;
+    let mut hands = vec![];
+    for (i, line) in contents.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let hand = serde_json::from_str(line)
+            .map_err(|e| eyre::eyre!("{} line {}: {}", path, i + 1, e))?;
+        hands.push(hand);
+    }
+    Ok(hands)
+}
+
+/// Rebuilds the shoes a history was dealt from. A shoe is created from its
+/// seed and dealt forward to each recorded position in turn, so the cards and
+/// the count are exactly what they were when the round was dealt, as long as
+/// the shoes are configured the same as when the history was recorded.
+pub struct ShoeTracker<F: Fn(u64) -> Result<Shoe, GameError>> {
+    new_shoe: F,
+    shoes: HashMap<u64, Shoe>,
+}
+
+impl<F: Fn(u64) -> Result<Shoe, GameError>> ShoeTracker<F> {
+    pub fn new(new_shoe: F) -> Self {
+        Self {
+            new_shoe,
+            shoes: HashMap::new(),
+        }
+    }
+    /// The shoe as it was at a recorded position.
+    pub fn at(&mut self, position: ShoePosition) -> Result<Shoe, GameError> {
+        let behind = |shoe: &Shoe| (shoe.shuffles(), shoe.position()) <= (position.shuffle, position.position);
+        let mut shoe = match self.shoes.remove(&position.seed) {
+            Some(shoe) if behind(&shoe) => shoe,
+            _ => (self.new_shoe)(position.seed)?,
+        };
+        while shoe.shuffles() < position.shuffle {
+            shoe.shuffle();
+        }
+        // A shoe smaller than the recorded one reshuffles before reaching
+        // the position, and the round it deals won't match the recording.
+        let shuffle = shoe.shuffles();
+        while shoe.position() < position.position && shoe.shuffles() == shuffle {
+            shoe.next();
+        }
+        self.shoes.insert(position.seed, shoe.clone());
+        Ok(shoe)
+    }
+}
+
+/// How a hand history came out when replayed.
+#[derive(Default)]
+pub struct Replay<'a> {
+    /// Rounds whose shoe couldn't be rebuilt to deal the recorded cards.
+    pub mismatched: usize,
+    pub same: usize,
+    /// Rounds that played out differently, with how they were replayed.
+    pub changes: Vec<(&'a RecordedHand, GameResult)>,
+    /// Units won over the rounds that could be rebuilt, as recorded and as
+    /// replayed.
+    pub recorded_net: f64,
+    pub replayed_net: f64,
+}
+
+/// Replays every round of a hand history from its rebuilt shoe with the given
+/// deviations, and the first move of every round forced if one is given, and
+/// compares each result with the recorded one.
+pub fn replay<'a>(
+    hands: &'a [RecordedHand],
+    shoes: &ShoeConfig,
+    deviations: &Deviations,
+    first_move: Option<&Move>,
+) -> Result<Replay<'a>, GameError> {
+    let mut tracker = ShoeTracker::new(|seed| shoes.shoe(seed));
+    let mut replay = Replay::default();
+    for hand in hands.iter() {
+        let mut cards = Arc::new(Mutex::new(tracker.at(hand.shoe)?));
+        let mut game = Game::new(&mut cards)?.with_deviations(deviations);
+        if let Some(action) = first_move {
+            game = game.with_first_move(action.clone());
+        }
+        game.start()?;
+        let result = GameResult::from(game).with_bet(hand.bet, hand.true_count);
+        if result.dealt()[..4] != hand.cards[..4.min(hand.cards.len())] {
+            replay.mismatched += 1;
+            continue;
+        }
+        replay.recorded_net += hand.units * hand.bet;
+        replay.replayed_net += result.net();
+        if result.dealt() == hand.cards && result.winner() == hand.winner && result.units() == hand.units {
+            replay.same += 1;
+        } else {
+            replay.changes.push((hand, result));
+        }
+    }
+    Ok(replay)
+}
@@ -0,0 +1,203 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::sync::{Arc, Mutex};
+
+use crate::deviations::{Deviation, Deviations, Play};
+use crate::shoe::Shoe;
+use crate::simulation::Simulation;
+use crate::stats::Tally;
+use crate::strategy::BASIC_STRATEGY;
+use crate::{Game, GameError, GameResult, Move};
+
+// True counts outside this range are too rare to estimate reliably and are
+// left out of the crossover fit.
+const MIN_TRUE_COUNT: i32 = -10;
+const MAX_TRUE_COUNT: i32 = 10;
+
+// Buckets with fewer samples than this for either action are ignored.
+const MIN_SAMPLES: u64 = 30;
+
+/// Outcomes of every action tried in every strategy situation, keyed by the
+/// situation and the floored true count at the time of the decision.
+#[derive(Debug, Default)]
+pub struct IndexGenerator {
+    outcomes: BTreeMap<String, BTreeMap<i32, HashMap<Move, Tally>>>,
+}
+
+/// The true count at which an alternative action becomes better than the
+/// basic strategy action in a situation.
+#[derive(Debug)]
+pub struct Crossover {
+    pub situation: String,
+    pub basic: Move,
+    pub alternative: Move,
+    pub index: f64,
+    /// Whether the alternative is better above the index rather than below.
+    pub at_or_above: bool,
+}
+
+impl IndexGenerator {
+    /// Tries every first action on the simulation's rounds, split across
+    /// threads.
+    pub fn simulate(simulation: &Simulation) -> Result<Self, GameError> {
+        simulation.run_merged(
+            |generator: &mut Self, shoe, rounds| generator.play_shoe(shoe, rounds),
+            Self::merge,
+        )
+    }
+    /// Plays rounds from a shoe. Before each round is played normally, every
+    /// legal first action is played out from a copy of the shoe with the
+    /// same cards, so all actions are compared on identical hands.
+    pub fn play_shoe(&mut self, shoe: Shoe, rounds: usize) -> Result<(), GameError> {
+        let mut cards = Arc::new(Mutex::new(shoe));
+        for _ in 0..rounds {
+            let before = {
+                let mut shoe = cards.lock().unwrap();
+                shoe.shuffle_if_needed();
+                shoe.clone()
+            };
+            let mut game = Game::new(&mut cards)?;
+            if !game.game_ended().0 {
+                let situation = game.situation();
+                let true_count = game.true_count().floor() as i32;
+                let mut actions = vec![Move::Hit, Move::Stand, Move::Double, Move::Surrender];
+                if game.has_pair() {
+                    actions.push(Move::Split);
+                }
+                for action in actions {
+                    let mut replay_cards = Arc::new(Mutex::new(before.clone()));
+                    let mut replay = Game::new(&mut replay_cards)?.with_first_move(action.clone());
+                    replay.start()?;
+                    let units = GameResult::from(replay).units();
+                    self.outcomes
+                        .entry(situation.clone())
+                        .or_default()
+                        .entry(true_count)
+                        .or_default()
+                        .entry(action)
+                        .or_default()
+                        .record(1.0, units);
+                }
+            }
+            game.start()?;
+        }
+        Ok(())
+    }
+    pub fn merge(&mut self, other: IndexGenerator) {
+        for (situation, buckets) in other.outcomes {
+            let ours = self.outcomes.entry(situation).or_default();
+            for (true_count, actions) in buckets {
+                let bucket = ours.entry(true_count).or_default();
+                for (action, tally) in actions {
+                    bucket.entry(action).or_default().merge(&tally);
+                }
+            }
+        }
+    }
+    /// Fits the difference in EV between each alternative and the basic
+    /// strategy action against the true count, weighting buckets by how
+    /// precisely they were measured, and solves for where the difference
+    /// crosses zero.
+    pub fn crossovers(&self) -> Vec<Crossover> {
+        let strat = BASIC_STRATEGY.lock().unwrap();
+        let mut crossovers = vec![];
+        for (situation, buckets) in self.outcomes.iter() {
+            let basic = match strat.get(situation.as_str()) {
+                Some(action) => action.clone(),
+                None => continue,
+            };
+            let mut alternatives: Vec<&Move> = buckets
+                .values()
+                .flat_map(|actions| actions.keys())
+                .filter(|a| **a != basic)
+                .collect();
+            alternatives.sort_by_key(|a| format!("{:?}", a));
+            alternatives.dedup();
+            for alternative in alternatives {
+                let points: Vec<(f64, f64, f64)> = buckets
+                    .range(MIN_TRUE_COUNT..=MAX_TRUE_COUNT)
+                    .filter_map(|(tc, actions)| {
+                        let b = actions.get(&basic)?;
+                        let a = actions.get(alternative)?;
+                        if a.hands < MIN_SAMPLES || b.hands < MIN_SAMPLES {
+                            return None;
+                        }
+                        // Inverse of the variance of the difference in means.
+                        let variance = a.std_dev().powi(2) / a.hands as f64
+                            + b.std_dev().powi(2) / b.hands as f64;
+                        if variance == 0.0 {
+                            return None;
+                        }
+                        Some((*tc as f64, a.mean() - b.mean(), 1.0 / variance))
+                    })
+                    .collect();
+                if let Some((index, slope)) = fit_crossover(&points) {
+                    crossovers.push(Crossover {
+                        situation: situation.clone(),
+                        basic: basic.clone(),
+                        alternative: alternative.clone(),
+                        index,
+                        at_or_above: slope > 0.0,
+                    });
+                }
+            }
+        }
+        crossovers
+    }
+}
+
+// Weighted least squares fit of (true count, EV difference, inverse variance)
+// points. Returns the true count where the fitted line crosses zero and its
+// slope, if the slope is at least two standard errors away from zero and the
+// count lies within the range the fit was made over.
+fn fit_crossover(points: &[(f64, f64, f64)]) -> Option<(f64, f64)> {
+    if points.len() < 3 {
+        return None;
+    }
+    let total: f64 = points.iter().map(|p| p.2).sum();
+    let mean_x = points.iter().map(|p| p.0 * p.2).sum::<f64>() / total;
+    let mean_y = points.iter().map(|p| p.1 * p.2).sum::<f64>() / total;
+    let covariance: f64 = points.iter().map(|p| p.2 * (p.0 - mean_x) * (p.1 - mean_y)).sum();
+    let variance: f64 = points.iter().map(|p| p.2 * (p.0 - mean_x).powi(2)).sum();
+    if variance == 0.0 || covariance == 0.0 {
+        return None;
+    }
+    let slope = covariance / variance;
+    if slope.abs() < 2.0 / variance.sqrt() {
+        return None;
+    }
+    let index = mean_x - mean_y / slope;
+    if index < MIN_TRUE_COUNT as f64 || index > MAX_TRUE_COUNT as f64 {
+        return None;
+    }
+    Some((index, slope))
+}
+
+/// Turns crossovers into a set of deviations, rounding each index to the
+/// nearest whole true count.
+pub fn to_deviations(crossovers: &[Crossover]) -> Deviations {
+    let plays = crossovers
+        .iter()
+        .map(|c| Deviation {
+            situation: c.situation.clone(),
+            index: c.index.round(),
+            at_or_above: c.at_or_above,
+            play: Play::Move(c.alternative.clone()),
+        })
+        .collect();
+    Deviations { plays }
+}
+
+/// Writes deviations in the file format read by `Deviations::from_file`.
+pub fn write_deviations(path: &str, deviations: &Deviations) -> eyre::Result<()> {
+    let mut contents = String::from("# Generated by simulation: <situation> <>= or <> <true count> <move>\n");
+    for d in deviations.plays.iter() {
+        if let Play::Move(action) = &d.play {
+            let op = if d.at_or_above { ">=" } else { "<" };
+            contents.push_str(&format!("{} {} {} {:?}\n", d.situation, op, d.index, action));
+        }
+    }
+    fs::write(path, contents)?;
+    Ok(())
+}
+
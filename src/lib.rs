@@ -0,0 +1,93 @@
+//! A blackjack engine: shoes dealt down to a cut card while a count is kept,
+//! rounds played by basic strategy, index plays or a [`Player`], and the
+//! statistics to judge a strategy or a bet ramp by.
+//!
+//! A round is dealt from a shoe shared behind a mutex, played out and turned
+//! into a [`GameResult`] that settles it:
+//!
+//! ```
+//! use std::sync::{Arc, Mutex};
+//!
+//! use blackjack_rs::count::CountingSystem;
+//! use blackjack_rs::shoe::Shoe;
+//! use blackjack_rs::stats::Stats;
+//! use blackjack_rs::{Deck, Game, GameResult};
+//!
+//! let shoe = Shoe::new(&Deck::builder(6), 0.75, CountingSystem::HiLo, 42)?;
+//! let mut cards = Arc::new(Mutex::new(shoe));
+//! let mut stats = Stats::default();
+//! for _ in 0..100 {
+//!     let mut game = Game::new(&mut cards)?;
+//!     game.start()?;
+//!     let result = GameResult::from(game).with_bet(2.0, 0.0);
+//!     println!("{:?} {:+}", result.winner(), result.net());
+//!     stats.record(&result);
+//! }
+//! assert_eq!(stats.games(), 100);
+//! # Ok::<(), blackjack_rs::GameError>(())
+//! ```
+//!
+//! A round can also be driven a step at a time, e.g. by a UI or a server
+//! making the player's decisions, with the strategy left to make the rest:
+//!
+//! ```
+//! use std::sync::{Arc, Mutex};
+//!
+//! use blackjack_rs::count::CountingSystem;
+//! use blackjack_rs::shoe::Shoe;
+//! use blackjack_rs::{Deck, Game, GameResult, Move, Phase};
+//!
+//! let shoe = Shoe::new(&Deck::builder(6), 0.75, CountingSystem::HiLo, 7)?;
+//! let mut cards = Arc::new(Mutex::new(shoe));
+//! let mut game = Game::new(&mut cards)?;
+//! loop {
+//!     match game.step_until_decision()? {
+//!         Phase::Insurance => game.insure(false)?,
+//!         Phase::PlayerTurn(_) => {
+//!             let action = if game.player_total() < 17 { Move::Hit } else { Move::Stand };
+//!             assert!(game.legal_moves().contains(&action));
+//!             game.apply(action)?
+//!         }
+//!         _ => break,
+//!     };
+//! }
+//! assert_eq!(game.phase(), Phase::Settled);
+//! println!("{:?}", GameResult::from(game).winner());
+//! # Ok::<(), blackjack_rs::GameError>(())
+//! ```
+//!
+//! Anything that can go wrong comes back as a [`GameError`] rather than a
+//! panic: a shoe too small to deal from or with a penetration out of range, a
+//! stacked shoe running out in the middle of a round (a regular shoe is
+//! reshuffled instead), or a move that isn't legal in the current phase.
+
+#[macro_use]
+extern crate lazy_static;
+
+pub mod bankroll;
+pub mod betting;
+pub mod cards;
+pub mod composition;
+pub mod count;
+pub mod deviations;
+pub mod efficiency;
+pub mod eor;
+pub mod error;
+pub mod game;
+mod hand;
+pub mod history;
+pub mod indices;
+pub mod ror;
+pub mod rules;
+pub mod session;
+pub mod shoe;
+pub mod simulation;
+pub mod situations;
+pub mod stats;
+pub mod strategy;
+pub mod systems;
+
+pub use cards::{Card, Deck};
+pub use error::GameError;
+pub use game::{Agent, Decision, Game, GameResult, Move, Phase, Player};
+pub use hand::hand_sum;
@@ -1,18 +1,33 @@
-#[macro_use]
-extern crate lazy_static;
-
 use std::sync::{Arc,Mutex};
-use std::thread;
-use rand::seq::SliceRandom;
-use rand::thread_rng;
+use rand::{thread_rng, Rng};
 
+use eyre::WrapErr;
 use structopt::StructOpt;
 
-mod strategy;
+use blackjack_rs::betting::{BetRamp, BetSizing, Kelly, Wong};
+use blackjack_rs::composition::{Changes, Composition};
+use blackjack_rs::count::{self, CountingSystem};
+use blackjack_rs::deviations::Deviations;
+use blackjack_rs::efficiency::{self, EorEstimator};
+use blackjack_rs::eor::EorCalculator;
+use blackjack_rs::history::{self, HistoryWriter};
+use blackjack_rs::indices::IndexGenerator;
+use blackjack_rs::session::{Session, SessionEnd, SessionLimits};
+use blackjack_rs::shoe::{self, Shoe, ShoeConfig};
+use blackjack_rs::simulation::Simulation;
+use blackjack_rs::situations;
+use blackjack_rs::stats::{self, Stats, Tally};
+use blackjack_rs::strategy;
+use blackjack_rs::{bankroll, betting, deviations, eor, indices, ror, systems};
+use blackjack_rs::{hand_sum, Card, Decision, Deck, Game, GameError, GameResult, Move};
 
-use strategy::BASIC_STRATEGY;
+mod drill;
+mod play;
+mod report;
+mod trainer;
+mod tui;
 
-#[derive(Debug, StructOpt)]
+#[derive(Debug, Clone, StructOpt)]
 pub struct Opt {
     // 6 decks for the game (used by Vegas tables).
     #[structopt(short = "d", default_value = "6")]
@@ -20,6 +35,228 @@ pub struct Opt {
     // Number of games to simulate.
     #[structopt(short = "n", default_value = "10000")]
     simulation_count: usize,
+    // Changes to the cards in the shoe, separated by spaces or commas: 5=0
+    // leaves no fives, T+8 adds eight tens, A-2 removes two aces and a card
+    // on its own removes one, so a list of cards already dealt can be given.
+    #[structopt(long = "composition")]
+    composition: Option<Changes>,
+    // Fraction of the shoe dealt before it is reshuffled.
+    #[structopt(short = "p", default_value = "0.75")]
+    penetration: f64,
+    // Counting system used to size bets (hi-lo, hi-opt-i, hi-opt-ii, omega-ii, zen).
+    #[structopt(long = "count", default_value = "hi-lo")]
+    counting_system: CountingSystem,
+    // Keep an ace side count, adding this many points to the running count per
+    // surplus ace for bets and deviations and subtracting them for insurance.
+    // Defaults to the level of the counting system.
+    #[structopt(long = "ace-side-count")]
+    ace_side_count: Option<Option<i32>>,
+    // Units added to the bet per point of true count.
+    #[structopt(long = "units-per-tc", default_value = "1")]
+    units_per_true_count: f64,
+    // Smallest and largest bet of the player's spread, in units.
+    #[structopt(long = "min-bet", default_value = "1")]
+    min_bet: f64,
+    #[structopt(long = "max-bet", default_value = "8")]
+    max_bet: f64,
+    // Table limits, in units.
+    #[structopt(long = "table-min", default_value = "1")]
+    table_min: f64,
+    #[structopt(long = "table-max", default_value = "100")]
+    table_max: f64,
+    // Play this many sessions from --bankroll units, each lasting up to
+    // --session-rounds rounds or until the stop-loss or win goal is reached.
+    #[structopt(long = "sessions")]
+    sessions: Option<usize>,
+    #[structopt(long = "bankroll", default_value = "1000")]
+    bankroll: f64,
+    #[structopt(long = "session-rounds", default_value = "1000")]
+    session_rounds: u64,
+    #[structopt(long = "stop-loss")]
+    stop_loss: Option<f64>,
+    #[structopt(long = "win-goal")]
+    win_goal: Option<f64>,
+    // Size session bets by Kelly (full, half, quarter or a fraction) off the
+    // advantage simulated at each true count instead of the bet ramp. Only
+    // sessions are sized this way, so it needs --sessions.
+    #[structopt(long = "kelly", requires = "sessions", parse(try_from_str = betting::parse_kelly_fraction))]
+    kelly: Option<f64>,
+    // Print analytic risk of ruin for --bankroll, the bankroll needed for a
+    // --target-ror risk and N0, from the simulated bet ramp results or from
+    // a given --win-rate and --std-dev per hand in units.
+    #[structopt(long = "ror")]
+    ror: bool,
+    #[structopt(long = "target-ror", default_value = "0.05")]
+    target_ror: f64,
+    #[structopt(long = "win-rate")]
+    win_rate: Option<f64>,
+    #[structopt(long = "std-dev")]
+    std_dev: Option<f64>,
+    // Compare betting systems (comma separated, or all) over the sessions:
+    // flat, martingale, paroli, fibonacci, dalembert, labouchere, oscar.
+    #[structopt(long = "betting-systems")]
+    betting_systems: Option<systems::Systems>,
+    // Back-count: only play once the true count reaches --wong-in, and leave
+    // when it drops below --wong-out or the shoe is shuffled.
+    #[structopt(long = "wong-in")]
+    wong_in: Option<f64>,
+    #[structopt(long = "wong-out", default_value = "0")]
+    wong_out: f64,
+    // Rounds dealt per hour at the table, used to report hourly rates.
+    #[structopt(long = "rounds-per-hour", default_value = "100")]
+    rounds_per_hour: f64,
+    // Index plays used instead of basic strategy at the right counts: none,
+    // i18, fab4, i18+fab4 or a path to a deviations file.
+    #[structopt(long = "deviations", default_value = "none")]
+    deviations: Deviations,
+    // Measure how much each deviation adds to the player's expectation.
+    #[structopt(long = "measure-deviations")]
+    measure_deviations: bool,
+    // Print betting correlation, playing efficiency, insurance correlation,
+    // SCORE, N0 and desirability index for the counting system.
+    #[structopt(long = "efficiency")]
+    efficiency: bool,
+    // Compute the effect of removal of each rank by simulation instead of
+    // running the usual summary, optionally writing them to a CSV file.
+    #[structopt(long = "eor")]
+    eor: bool,
+    #[structopt(long = "eor-out")]
+    eor_out: Option<String>,
+    // Derive deviation indices by simulation instead of running the usual
+    // summary, optionally writing them to a deviations file.
+    #[structopt(long = "generate-indices")]
+    generate_indices: bool,
+    #[structopt(long = "indices-out")]
+    indices_out: Option<String>,
+    // Keep simulating batches of -n games until the 95% confidence interval
+    // of the basic strategy house edge is narrower than this many percent.
+    #[structopt(long = "tolerance")]
+    tolerance: Option<f64>,
+    // Stop simulating batches for --tolerance after this many games, even if
+    // the interval is still wider.
+    #[structopt(long = "max-rounds", default_value = "100000000")]
+    max_rounds: usize,
+    // Print the units won per hand for each starting hand against each
+    // dealer up card, optionally writing the outcomes to a CSV file.
+    #[structopt(long = "situations")]
+    situations: bool,
+    #[structopt(long = "situations-out")]
+    situations_out: Option<String>,
+    // Write every hand played to this file as newline delimited JSON.
+    #[structopt(long = "history")]
+    history: Option<String>,
+    // Strategy chart loaded over basic strategy, with a situation and a move
+    // per line, e.g. "16,10 Stand".
+    #[structopt(long = "chart")]
+    chart: Option<String>,
+    // Play hands at the terminal with a chip stack of --bankroll units
+    // instead of simulating.
+    #[structopt(long = "play")]
+    play: bool,
+    // Draw --play as a table in a full-screen terminal UI, and show a live
+    // dashboard while simulating instead of waiting for the report.
+    #[structopt(long = "tui")]
+    tui: bool,
+    // Practice counting instead of simulating: cards are dealt
+    // --drill-speed a second and the running and true count are asked for
+    // at random points.
+    #[structopt(long = "drill")]
+    drill: bool,
+    #[structopt(long = "drill-speed", default_value = "1.0")]
+    drill_speed: f64,
+    // Practice basic strategy at the terminal instead of simulating, asking
+    // tough situations more often with --train-tough. Accuracy per
+    // situation is kept in --trainer-file across sessions.
+    #[structopt(long = "train")]
+    train: bool,
+    #[structopt(long = "train-tough")]
+    train_tough: bool,
+    #[structopt(long = "trainer-file", default_value = "trainer.json")]
+    trainer_file: String,
+    // Deal rounds from a stacked shoe instead of simulating: a file of cards
+    // or a string like "A T 8 8 6", dealt in order two cards to the dealer
+    // (the first is the up card), two to the player, then as drawn.
+    #[structopt(long = "stack")]
+    stack: Option<String>,
+    // Replay the rounds of a hand history with the configured strategy
+    // instead of simulating, comparing each result with the recorded one.
+    // The shoe options must match those the history was recorded with.
+    #[structopt(long = "replay")]
+    replay: Option<String>,
+    // Force the first decision of every replayed round.
+    #[structopt(long = "replay-first-move")]
+    replay_first_move: Option<Move>,
+    // Output format of the simulation results: text, or json or csv for the
+    // configuration, summary and breakdowns in a stable schema.
+    #[structopt(long = "format", default_value = "text")]
+    format: report::Format,
+    // Seed for shuffling shoes. Runs with the same seed deal the same cards.
+    #[structopt(short = "s")]
+    seed: Option<u64>,
+}
+
+impl Opt {
+    pub fn ace_points(&self) -> Option<i32> {
+        self.ace_side_count
+            .map(|points| points.unwrap_or_else(|| self.counting_system.level()))
+    }
+    // The decks with any changes to their composition applied.
+    pub fn composition(&self) -> Composition {
+        let composition = Deck::builder(self.num_decks);
+        match &self.composition {
+            Some(changes) => composition.apply(changes),
+            None => composition,
+        }
+    }
+    pub fn shoe_config(&self) -> ShoeConfig {
+        ShoeConfig {
+            composition: self.composition(),
+            penetration: self.penetration,
+            system: self.counting_system,
+            ace_side_count: self.ace_points(),
+        }
+    }
+    // A shuffled shoe for the configured decks and counting system.
+    pub fn shoe(&self, seed: u64) -> Result<Shoe, GameError> {
+        self.shoe_config().shoe(seed)
+    }
+    // The configured run of -n games.
+    pub fn simulation(&self) -> Simulation {
+        Simulation {
+            shoes: self.shoe_config(),
+            seed: self.seed.unwrap_or_default(),
+            rounds: self.simulation_count,
+            ramp: self.bet_ramp(),
+            wong: self.wong(),
+            deviations: self.deviations.clone(),
+        }
+    }
+    pub fn wong(&self) -> Option<Wong> {
+        self.wong_in.map(|entry| Wong {
+            entry,
+            exit: self.wong_out,
+        })
+    }
+    pub fn bet_ramp(&self) -> BetRamp {
+        BetRamp {
+            units_per_true_count: self.units_per_true_count,
+            min_units: self.min_bet,
+            max_units: self.max_bet,
+            table_min: self.table_min,
+            table_max: self.table_max,
+        }
+    }
+    // Checks the table rules describe a game that can be dealt, and that a
+    // tolerance can be reached, so bad flags are reported up front rather
+    // than partway through a simulation.
+    pub fn validate(&self) -> eyre::Result<()> {
+        self.shoe_config().validate()?;
+        self.bet_ramp().validate()?;
+        if let Some(tolerance) = self.tolerance.filter(|t| !(t.is_finite() && *t > 0.0)) {
+            eyre::bail!("--tolerance {} must be a percentage above 0", tolerance);
+        }
+        Ok(())
+    }
 }
 
 // Goal: spawn tons of games of blackjack in the background using
@@ -27,352 +264,633 @@ pub struct Opt {
 // Observe the performance of the commonly touted "basic strategy" from the results.
 #[tokio::main]
 async fn main() -> eyre::Result<()> {
-    let opts = Opt::from_args();
-    let deck = Deck::new(opts.num_decks);
-    let cards = Arc::new(Mutex::new(deck.cards.into_iter().cycle()));
-
-    let mut handlers = vec![];
-    for _ in 0..opts.simulation_count {
-        let mut cards = cards.clone();
-        handlers.push(thread::spawn(move || {
-            let mut game = Game::new(&mut cards);
-            game.start();
-            GameResult::from(game)
-        }));
-    }
-    let game_results: Vec<GameResult> = handlers.into_iter()
-        .map(|handler| handler.join().unwrap())
-        .collect();
-
-    let mut player_w: f64 = 0.0;
-    let mut dealer_w: f64 = 0.0;
-    let mut draw: f64 = 0.0;
-    let tot = game_results.len() as f64;
-    game_results
-        .into_iter()
-        .for_each(|g| {
-            match g.winner {
-                Some(Agent::Player) => player_w += 1.0,
-                Some(Agent::Dealer) => dealer_w += 1.0,
-                None => draw += 1.0,
-            }
-        });
+    let mut opts = Opt::from_args();
+    let seed = *opts.seed.get_or_insert_with(|| thread_rng().gen());
+    opts.validate()?;
+    if let Some(path) = &opts.chart {
+        strategy::load_chart(path)?;
+    }
+    if opts.generate_indices {
+        return generate_indices(&opts);
+    }
+    if opts.eor {
+        return compute_eors(&opts);
+    }
+    if opts.play && opts.tui {
+        return tui::play(&opts, opts.shoe(seed)?);
+    }
+    if opts.play {
+        return play::play(&opts, opts.shoe(seed)?);
+    }
+    if opts.drill {
+        return drill::drill(&opts, seed, opts.drill_speed);
+    }
+    if opts.train {
+        return trainer::train(&opts, seed, opts.train_tough, &opts.trainer_file);
+    }
+    if let Some(stack) = &opts.stack {
+        return play_stack(&opts, stack);
+    }
+    if let Some(path) = &opts.replay {
+        return replay_history(&opts, path);
+    }
+    if let (true, Some(win_rate), Some(std_dev)) = (opts.ror, opts.win_rate, opts.std_dev) {
+        print_risk_of_ruin(&opts, win_rate, std_dev);
+        return Ok(());
+    }
+    let history = match &opts.history {
+        Some(path) => Some(HistoryWriter::create(path)?),
+        None => None,
+    };
+    let simulation = opts.simulation();
+    let stats = match opts.tolerance {
+        _ if opts.tui => tui::dashboard(&opts, history.as_ref())?,
+        Some(tolerance) => simulation.run_until_converged(tolerance, opts.max_rounds, history.as_ref())?,
+        None => simulation.run(history.as_ref())?,
+    };
+    if let Some(history) = &history {
+        history.finish()?;
+    }
+    let tot = stats.games().max(1) as f64;
+    if opts.format != report::Format::Text {
+        let report = report::Report::new(&opts, &stats);
+        return match opts.format {
+            report::Format::Json => report::write_json(std::io::stdout(), &report),
+            _ => report::write_csv(std::io::stdout(), &report),
+        };
+    }
 
     println!("Blackjack strategy simulator, sample game played:");
-    let mut cards = cards.clone();
-    let mut game = Game::new(&mut cards);
-    game.start();
-    println!("");
-    println!("Winner: {:?}", game.winner);
-    println!("Dealer hand: {:?} = {}", game.dealer_hand, game.dealer_total);
-    println!("Player move(s): {:?}", game.player_moves);
-    println!("Player hand(s): {:?} = {}", game.player_hands, game.player_total);
-    println!("");
+    let mut cards = Arc::new(Mutex::new(opts.shoe(seed)?));
+    let mut game = Game::new(&mut cards)?;
+    game.start()?;
+    println!();
+    println!("Winner: {:?}", game.winner());
+    println!("Dealer hand: {:?} = {}", game.dealer_hand(), game.dealer_total());
+    println!("Player move(s): {:?}", game.player_moves());
+    println!("Player hand(s): {:?} = {}", game.player_hands(), game.player_total());
+    println!();
 
     println!("*********************************************");
     println!("* Testing effectiveness of 'basic strategy' *");
     println!("*********************************************");
     println!("Deck size: {}", opts.num_decks);
-    println!("Simulated games: {}", opts.simulation_count);
-    println!("Player wins: {}%", player_w / tot * 100.0);
-    println!("Dealer wins: {}%", dealer_w / tot * 100.0);
-    println!("Ties: {}%", draw / tot * 100.0);
+    if opts.composition.is_some() {
+        println!("Shoe composition: {}", opts.composition());
+    }
+    println!("Simulated games: {}", stats.rounds_observed);
+    println!("Seed: {}", seed);
+    println!("Margins are 95% confidence intervals, SE the standard error");
+    if let Some(tolerance) = opts.tolerance {
+        println!(
+            "House edge confidence interval: {:.3}% wide (tolerance {}%)",
+            stats.edge_interval_width(),
+            tolerance,
+        );
+        if !stats.converged(tolerance) {
+            println!("Tolerance not reached within --max-rounds {}", opts.max_rounds);
+        }
+    }
+    println!("Player wins: {}", proportion(stats.player_wins, stats.games()));
+    println!("Dealer wins: {}", proportion(stats.dealer_wins, stats.games()));
+    println!("Ties: {}", proportion(stats.ties, stats.games()));
+    print_tally(&stats.flat);
+    println!();
+
+    let ramp = opts.bet_ramp();
+    println!("*********************************************");
+    println!("* Counting cards and spreading bets         *");
+    println!("*********************************************");
+    println!("Counting system: {}", opts.counting_system);
+    if let Some(points) = opts.ace_points() {
+        println!("Ace side count: {} point(s) per surplus ace", points);
+    }
+    println!("Penetration: {:.0}%", opts.penetration * 100.0);
+    println!(
+        "Bet ramp: {} unit(s) per true count, spread {}-{}, table limits {}-{}",
+        ramp.units_per_true_count, ramp.min_units, ramp.max_units, ramp.table_min, ramp.table_max,
+    );
+    println!(
+        "Average bet: {}",
+        estimate(stats.spread.mean_wager(), stats.spread.mean_wager_std_error(), 2, " units"),
+    );
+    print_tally(&stats.spread);
+    println!("Player advantage by true count:");
+    for (true_count, tally) in stats.by_true_count.iter() {
+        println!(
+            "  TC {:>3}: {:>6.2}% of hands, advantage {:>6.2}% +/- {:.2}%",
+            true_count,
+            tally.hands as f64 / tot * 100.0,
+            tally.advantage() * 100.0,
+            stats::Z_95 * tally.advantage_std_error() * 100.0,
+        );
+    }
+
+    if let Some(wong) = opts.wong() {
+        let observed = stats.rounds_observed.max(1) as f64;
+        let hours = observed / opts.rounds_per_hour;
+        let played = stats::proportion_std_error(stats.games(), stats.rounds_observed);
+        let per_round = stats.spread.std_error_over(stats.rounds_observed);
+        println!();
+        println!("*********************************************");
+        println!("* Back-counting                             *");
+        println!("*********************************************");
+        println!("Enter at TC >= {}, leave below TC {}", wong.entry, wong.exit);
+        println!("Rounds observed: {}", stats.rounds_observed);
+        println!(
+            "Hands played: {}, {} of rounds",
+            stats.games(),
+            proportion(stats.games(), stats.rounds_observed),
+        );
+        println!(
+            "Hands played per hour: {}",
+            estimate(stats.games() as f64 / hours, played * opts.rounds_per_hour, 1, ""),
+        );
+        println!(
+            "Win rate per hand observed: {}",
+            estimate(stats.spread.net / observed, per_round, 4, " units"),
+        );
+        println!(
+            "Win rate per hour: {}",
+            estimate(stats.spread.net / hours, per_round * opts.rounds_per_hour, 2, " units"),
+        );
+    }
+
+    if !opts.deviations.plays.is_empty() {
+        println!();
+        println!("*********************************************");
+        println!("* Playing deviations from basic strategy    *");
+        println!("*********************************************");
+        println!("Deviations: {}", opts.deviations.plays.len());
+        println!(
+            "Rounds with a deviation played: {}",
+            proportion(stats.deviation_gain.hands, stats.games()),
+        );
+        println!(
+            "Gain in player advantage over basic strategy: {}",
+            estimate(
+                stats.deviation_gain.net / tot * 100.0,
+                stats.deviation_gain.std_error_over(stats.games()) * 100.0,
+                3,
+                "%",
+            ),
+        );
+    }
+    if opts.situations {
+        println!();
+        println!("*********************************************");
+        println!("* Outcomes by starting hand and up card     *");
+        println!("*********************************************");
+        println!("Units won per 100 hands, flat betting:");
+        situations::print_grid(&stats.by_situation);
+    }
+    if let Some(path) = &opts.situations_out {
+        situations::write_csv(path, &stats.by_situation)?;
+        println!("Wrote outcomes by situation to {}", path);
+    }
+    if opts.measure_deviations {
+        measure_deviations(&opts)?;
+    }
+    if opts.efficiency {
+        print_efficiency(&opts, &stats)?;
+    }
+    if opts.ror {
+        print_risk_of_ruin(&opts, stats.spread.mean(), stats.spread.std_dev());
+    }
+    if let Some(count) = opts.sessions {
+        let limits = SessionLimits {
+            stop_loss: opts.stop_loss,
+            win_goal: opts.win_goal,
+            rounds: opts.session_rounds,
+        };
+        let (title, sizing) = match opts.kelly {
+            Some(fraction) => (
+                format!("{}x Kelly", fraction),
+                BetSizing::Kelly(Kelly::new(
+                    fraction,
+                    opts.table_min,
+                    opts.table_max,
+                    &stats.by_true_count,
+                    &stats.flat,
+                )),
+            ),
+            None => ("bet ramp".to_string(), BetSizing::Ramp(opts.bet_ramp())),
+        };
+        print_sessions(&opts, &title, &simulation.sessions(count, opts.bankroll, limits, sizing)?);
+        if let Some(systems) = &opts.betting_systems {
+            compare_betting_systems(&opts, &systems.0, count, limits)?;
+        }
+    }
     Ok(())
 }
 
-#[derive(Debug,PartialEq,Clone,Copy)]
-pub enum Card {
-    Two,
-    Three,
-    Four,
-    Five,
-    Six,
-    Seven,
-    Eight,
-    Nine,
-    Ten,
-    J,
-    Q,
-    K,
-    A,
+// Prints closed form risk of ruin estimates for a win rate and standard
+// deviation per hand.
+fn print_risk_of_ruin(opts: &Opt, win_rate: f64, std_dev: f64) {
+    println!();
+    println!("*********************************************");
+    println!("* Risk of ruin                              *");
+    println!("*********************************************");
+    println!("Win rate per hand: {:.4} units", win_rate);
+    println!("Standard deviation per hand: {:.4} units", std_dev);
+    println!("Bankroll: {} units", opts.bankroll);
+    println!(
+        "Risk of ruin: {:.4}%",
+        ror::risk_of_ruin(win_rate, std_dev, opts.bankroll) * 100.0,
+    );
+    println!(
+        "Risk of ruin within {} hands: {:.4}%",
+        opts.session_rounds,
+        ror::trip_risk_of_ruin(win_rate, std_dev, opts.bankroll, opts.session_rounds as f64) * 100.0,
+    );
+    println!(
+        "Bankroll for {:.2}% risk of ruin: {:.1} units",
+        opts.target_ror * 100.0,
+        ror::bankroll_for_risk(win_rate, std_dev, opts.target_ror),
+    );
+    println!("N0: {:.0} hands", ror::n0(win_rate, std_dev));
 }
 
-// Turns a card into its u8 representation, as several face cards
-// can all map to 10. For now, treats aces as mapping to 11.
-impl From<&Card> for u8 {
-    fn from(c: &Card) -> Self {
-        match c {
-            Card::Two => 2,
-            Card::Three => 3,
-            Card::Four => 4,
-            Card::Five => 5,
-            Card::Six => 6,
-            Card::Seven => 7,
-            Card::Eight => 8,
-            Card::Nine => 9,
-            Card::Ten => 10,
-            Card::J => 10,
-            Card::Q => 10,
-            Card::K => 10,
-            Card::A => 11,
+// Plays the same number of sessions with each betting system and prints how
+// they compare. The edge per unit wagered doesn't move no matter the system;
+// only the size of the bets and the spread of the results do.
+fn compare_betting_systems(
+    opts: &Opt,
+    systems: &[systems::System],
+    count: usize,
+    limits: SessionLimits,
+) -> eyre::Result<()> {
+    println!();
+    println!("*********************************************");
+    println!("* Betting systems                           *");
+    println!("*********************************************");
+    println!("Table limits: {}-{} units", opts.table_min, opts.table_max);
+    println!(
+        "{:<14} {:>8} {:>13} {:>12} {:>10} {:>8} {:>9}",
+        "System", "Avg bet", "Edge/wagered", "Mean result", "SD result", "Ruin", "Win goal",
+    );
+    for system in systems {
+        let sizing = BetSizing::Progression {
+            system: *system,
+            table_min: opts.table_min,
+            table_max: opts.table_max,
+        };
+        let sessions = opts.simulation().sessions(count, opts.bankroll, limits, sizing)?;
+        let total = sessions.len().max(1) as f64;
+        let mut results = Tally::default();
+        let mut wagered = 0.0;
+        let mut rounds = 0;
+        for session in sessions.iter() {
+            results.record(session.bankroll.wagered, session.result());
+            wagered += session.bankroll.wagered;
+            rounds += session.bankroll.rounds;
         }
+        let ended = |end: SessionEnd| sessions.iter().filter(|s| s.end == end).count() as f64 / total;
+        println!(
+            "{:<14} {:>8.2} {:>12.2}% {:>12.2} {:>10.2} {:>7.2}% {:>8.2}%",
+            system.to_string(),
+            wagered / rounds.max(1) as f64,
+            results.advantage() * 100.0,
+            results.mean(),
+            results.std_dev(),
+            ended(SessionEnd::Ruin) * 100.0,
+            ended(SessionEnd::WinGoal) * 100.0,
+        );
     }
+    Ok(())
 }
 
-// Creates a deck instance out of an allowed card set and shuffles it.
-#[derive(Debug)]
-pub struct Deck {
-    cards: Vec<Card>,
+// Prints the distribution of session results, drawdowns and how the
+// sessions ended.
+fn print_sessions(opts: &Opt, title: &str, sessions: &[Session]) {
+    let total = sessions.len().max(1) as f64;
+    let ended = |end: SessionEnd| sessions.iter().filter(|s| s.end == end).count();
+    let mut results: Vec<f64> = sessions.iter().map(|s| s.result()).collect();
+    let mut growth: Vec<f64> = sessions.iter().map(|s| s.bankroll.growth_per_100()).collect();
+    let mut drawdowns: Vec<f64> = sessions.iter().map(|s| s.bankroll.max_drawdown).collect();
+    let mut doubled: Vec<f64> = sessions
+        .iter()
+        .filter_map(|s| s.rounds_to_double.map(|r| r as f64))
+        .collect();
+    println!();
+    println!("*********************************************");
+    println!("* Bankroll and sessions                     *");
+    println!("*********************************************");
+    println!("Bet sizing: {}", title);
+    println!("Starting bankroll: {} units", opts.bankroll);
+    println!("Sessions: {}", sessions.len());
+    println!(
+        "Ended by stop-loss: {}, win goal: {}, ruin: {}, round limit: {}",
+        ended(SessionEnd::StopLoss),
+        ended(SessionEnd::WinGoal),
+        ended(SessionEnd::Ruin),
+        ended(SessionEnd::RoundLimit),
+    );
+    println!("Risk of ruin: {}", proportion(ended(SessionEnd::Ruin) as u64, sessions.len() as u64));
+    println!(
+        "Session result: 5% {:.1}, 25% {:.1}, median {:.1}, 75% {:.1}, 95% {:.1} units",
+        bankroll::quantile(&mut results, 0.05),
+        bankroll::quantile(&mut results, 0.25),
+        bankroll::quantile(&mut results, 0.5),
+        bankroll::quantile(&mut results, 0.75),
+        bankroll::quantile(&mut results, 0.95),
+    );
+    println!(
+        "Median growth per 100 rounds: {:.3}%",
+        bankroll::quantile(&mut growth, 0.5) * 100.0,
+    );
+    if doubled.is_empty() {
+        println!("Time to double: no session doubled its bankroll");
+    } else {
+        println!(
+            "Time to double: {:.2}% of sessions, median {:.0} rounds",
+            doubled.len() as f64 / total * 100.0,
+            bankroll::quantile(&mut doubled, 0.5),
+        );
+    }
+    println!(
+        "Max drawdown: 25% {:.2}%, median {:.2}%, 75% {:.2}%, worst {:.2}%",
+        bankroll::quantile(&mut drawdowns, 0.25) * 100.0,
+        bankroll::quantile(&mut drawdowns, 0.5) * 100.0,
+        bankroll::quantile(&mut drawdowns, 0.75) * 100.0,
+        bankroll::quantile(&mut drawdowns, 1.0) * 100.0,
+    );
 }
 
-impl Deck {
-    pub fn new(num_decks: usize) -> Self {
-        let card_set = [
-            Card::A,
-            Card::Two,
-            Card::Three,
-            Card::Four,
-            Card::Five,
-            Card::Six,
-            Card::Seven,
-            Card::Eight,
-            Card::Nine,
-            Card::Ten,
-            Card::J,
-            Card::Q,
-            Card::K,
-        ];
-        // Create multiple multiple decks if desired.
-        let mut cards: Vec<Card> = card_set
-            .iter()
-            .cycle()
-            .take(card_set.len()*num_decks)
-            .cloned()
-            .collect();
+// Prints the standard metrics for comparing counting systems. Betting and
+// insurance correlations are computed against published effects of removal,
+// betting correlation and playing efficiency also against effects of removal
+// estimated from simulated rounds under the configured rules and decks, and
+// SCORE, N0 and desirability index from the simulated bet ramp results.
+fn print_efficiency(opts: &Opt, stats: &Stats) -> eyre::Result<()> {
+    let estimator = EorEstimator::simulate(&opts.simulation(), &Deviations::illustrious_18())?;
+    let tags = opts.counting_system.tags();
 
-        cards.shuffle(&mut thread_rng());
-        
-        Self {
-            cards,
-        } 
+    println!();
+    println!("*********************************************");
+    println!("* Counting system efficiency                *");
+    println!("*********************************************");
+    println!("Counting system: {} {:?}", opts.counting_system, tags);
+    println!(
+        "Betting correlation: {:.3} (published EORs)",
+        efficiency::correlation(&tags, &efficiency::BETTING_EOR),
+    );
+    if let Some(points) = opts.ace_points() {
+        // Counting each ace seen against the side count works out the same
+        // as tagging aces with the side count points.
+        let mut side_counted = tags;
+        side_counted[count::RANKS - 1] -= points;
+        println!(
+            "Betting correlation: {:.3} (published EORs, with ace side count)",
+            efficiency::correlation(&side_counted, &efficiency::BETTING_EOR),
+        );
+    }
+    if let Some(eors) = estimator.betting.eors() {
+        println!(
+            "Betting correlation: {:.3} (simulated EORs)",
+            efficiency::correlation(&tags, &eors),
+        );
+        println!("Simulated EORs (2-9, T, A): {:.2?}", eors);
     }
-    pub fn shuffle(&mut self) {
-        self.cards.shuffle(&mut thread_rng());
+    match estimator.playing_efficiency(&tags) {
+        Some(pe) => println!("Playing efficiency: {:.3} (Illustrious 18 decisions)", pe),
+        None => println!("Playing efficiency: not enough decisions simulated"),
     }
+    println!(
+        "Insurance correlation: {:.3}",
+        efficiency::correlation(&tags, &efficiency::INSURANCE_EOR),
+    );
+    println!("SCORE: {:.2}", efficiency::score(&stats.spread));
+    println!("N0: {:.0} hands", efficiency::n0(&stats.spread));
+    println!("Desirability index: {:.2}", efficiency::desirability_index(&stats.spread));
+    Ok(())
 }
 
-// A valid move a player can make in the game.
-#[derive(Debug,Clone)]
-pub enum Move {
-    Double,
-    Stand,
-    Hit,
-    Split,
+// Plays the games with each deviation on its own, printing how much each one
+// adds to the player's advantage.
+fn measure_deviations(opts: &Opt) -> eyre::Result<()> {
+    println!();
+    println!("Gain in player advantage per deviation:");
+    for (i, deviation) in opts.deviations.plays.iter().enumerate() {
+        let only = Simulation {
+            deviations: opts.deviations.only(i),
+            ..opts.simulation()
+        };
+        let stats = only.run(None)?;
+        let games = stats.games().max(1) as f64;
+        let gain = &stats.deviation_gain;
+        let action = match &deviation.play {
+            deviations::Play::Move(action) => format!("{:?}", action),
+            deviations::Play::Insurance => "Insure".to_string(),
+        };
+        println!(
+            "  {:>9} {} {:>3} {:<9} played in {:>6.2}% of rounds, gain {:>+7.3}% +/- {:.3}%",
+            deviation.situation,
+            if deviation.at_or_above { ">=" } else { "< " },
+            deviation.index,
+            action,
+            gain.hands as f64 / games * 100.0,
+            gain.net / games * 100.0,
+            stats::Z_95 * gain.std_error_over(stats.games()) * 100.0,
+        );
+    }
+    Ok(())
 }
 
-// The kinds of players in the game.
-#[derive(Debug,PartialEq,Copy,Clone)]
-pub enum Agent {
-    Dealer,
-    Player,
+// Plays every legal first action in every situation across many shoes and
+// prints the true count at which each alternative overtakes basic strategy.
+fn generate_indices(opts: &Opt) -> eyre::Result<()> {
+    let crossovers = IndexGenerator::simulate(&opts.simulation())?.crossovers();
+    println!("*********************************************");
+    println!("* Deviation indices derived by simulation   *");
+    println!("*********************************************");
+    println!("Counting system: {}", opts.counting_system);
+    println!("Simulated games: {}", opts.simulation_count);
+    for c in crossovers.iter() {
+        println!(
+            "  {:>9}: {:<9} instead of {:<9} at TC {} {:+.1}",
+            c.situation,
+            format!("{:?}", c.alternative),
+            format!("{:?}", c.basic),
+            if c.at_or_above { ">=" } else { "< " },
+            c.index,
+        );
+    }
+    if let Some(path) = &opts.indices_out {
+        indices::write_deviations(path, &indices::to_deviations(&crossovers))?;
+        println!("Wrote {} deviations to {}", crossovers.len(), path);
+    }
+    Ok(())
 }
 
-// A game instance.
-#[derive(Debug)]
-pub struct Game<'a, T: Iterator> {
-    deck: &'a mut Arc<Mutex<T>>,
-    dealer_hand: Vec<Card>,
-    dealer_total: u8,
-    player_moves: Vec<Move>,
-    player_hands: (Vec<Card>, Vec<Card>),
-    player_total: u8,
-    dealer_beats_player: bool,
-    winner: Option<Agent>,
-}
+// Prints the effect of removing one card of each rank from the shoe on the
+// player's expectation, along with how well the counting system tracks them.
+fn compute_eors(opts: &Opt) -> eyre::Result<()> {
+    let calculator = EorCalculator::simulate(&opts.simulation())?;
+    let eors = calculator.eors();
+    let errors = calculator.std_errors();
+    let total_cards = opts.composition().total();
+    let scaled = eor::single_deck_equivalent(&eors, total_cards);
 
-impl <'a, T> Game<'a, T> where T: Iterator<Item=Card> {
-    pub fn new(
-        cards: &'a mut Arc<Mutex<T>>,
-    ) -> Self {
-        let dealer_hand = take_two(cards);
-        let player_hand = take_two(cards);
-        let dealer_total = u8::from(dealer_hand.first().unwrap());
-        let player_total = hand_sum(&player_hand);
-        Self {
-            deck: cards,
-            dealer_hand,
-            dealer_total,
-            player_hands: (player_hand, vec![]),
-            player_total,
-            player_moves: vec![],
-            dealer_beats_player: false,
-            winner: None,
-        }
+    println!("*********************************************");
+    println!("* Effects of removal                        *");
+    println!("*********************************************");
+    println!("Deck size: {}", opts.num_decks);
+    if opts.composition.is_some() {
+        println!("Shoe composition: {}", opts.composition());
     }
-    pub fn start(&mut self) {
-        let mut player_done = false;
-        let mut dealer_revealed = false;
-        loop {
-            match &self.game_ended() {
-                (false, _) => {
-                    // Player moves.
-                    if !player_done {
-                        let action = self.act();
-                        match action {
-                            Move::Hit => {
-                                let card = self.next_card();
-                                self.player_hands.0.push(card);
-                                self.player_total += u8::from(&card);
-                            },
-                            Move::Double => {
-                                let card = self.next_card();
-                                self.player_hands.0.push(card);
-                                self.player_total += u8::from(&card);
-                                player_done = true;
-                            },
-                            Move::Split => {
-                                // Pop a card from the hand and push it to the second hand.
-                                let splitter = self.player_hands.0.pop().unwrap();
-                                self.player_hands.1.push(splitter);
-
-                                // Hit both hands.
-                                let card = self.next_card();
-                                self.player_hands.0.push(card);
-                                let card = self.next_card();
-                                self.player_hands.1.push(card);
-
-                                // End the player action.
-                                self.player_total += u8::from(&card);
-                                player_done = true;
-                            },
-                            Move::Stand => {
-                                player_done = true;
-                            },
-                        }
-                        self.player_moves.push(action);
-                    } else {
-                        if !dealer_revealed {
-                            self.dealer_total += u8::from(self.dealer_hand.last().unwrap());
-                            dealer_revealed = true;
-                            continue;
-                        }
-                        // Dealer moves.
-                        let card = self.next_card();
-                        self.dealer_hand.push(card);
-                        self.dealer_total += u8::from(&card);
-
-                        // TODO: Consider split.
-                        if self.dealer_total <= 21 && self.dealer_total > self.player_total {
-                            self.dealer_beats_player = true;
-                        }
-                    }
-                },
-                (true, None) => {
-                    self.winner = None;
-                    return;
-                }
-                (true, Some(agent)) => {
-                    self.winner = Some(agent.clone());
-                    return;
-                }
-            }
-        }
+    println!("Simulated rounds: {}", opts.simulation_count);
+    println!("Full shoe player advantage: {:.3}%", calculator.full.mean() * 100.0);
+    println!("Rank      EOR   +/- std err   single deck");
+    for rank in 0..count::RANKS {
+        println!(
+            "{:>4} {:>+8.4}% {:>8.4}%    {:>+8.3}%",
+            eor::RANK_NAMES[rank], eors[rank], errors[rank], scaled[rank],
+        );
     }
-    pub fn next_card(&self) -> Card {
-        let mut deck = self.deck
-            .lock()
-            .unwrap();
-        deck.next().unwrap()
+    println!(
+        "Betting correlation of {}: {:.3}",
+        opts.counting_system,
+        efficiency::correlation(&opts.counting_system.tags(), &eors),
+    );
+    if let Some(path) = &opts.eor_out {
+        eor::write_csv(path, &calculator, total_cards)?;
+        println!("Wrote effects of removal to {}", path);
     }
-    pub fn act(&self) -> Move {
-        let dealer_up_card = u8::from(self.dealer_hand.first().unwrap());
-        let player_sum = hand_sum(&self.player_hands.0);
-
-        // Do cards match? If so, then determine pair strategy.
-        let has_pair = self.player_hands.0.windows(2).all(|c| c[0] == c[1]);
-        if self.player_hands.0.len() == 2 && has_pair {
-            let hand = &self.player_hands.0;
-            let first = u8::from(hand.first().unwrap());
-            let key = format!("{},{},{}", first, first, dealer_up_card);
-            let strat = BASIC_STRATEGY.lock().unwrap();
-            match strat.get(&key.as_str()) {
-                Some(action) => return action.clone(),
-                None => panic!("no move found for key {}", key)
-            }
-        }
-
-        // Always hit if < 5.
-        if player_sum < 5 {
-            return Move::Hit;
-        }
-
-        // Always stand if sum > 17.
-        if player_sum > 17 {
-            return Move::Stand;
-        }
+    Ok(())
+}
 
-        let key = format!("{},{}", player_sum, dealer_up_card);
-        let strat = BASIC_STRATEGY.lock().unwrap();
-        match strat.get(&key.as_str()) {
-            Some(action) => action.clone(),
-            None => panic!("no move found for situation {}", key)
-        }
-    }
-    pub fn game_ended(&self) -> (bool, Option<Agent>) {
-        // Special rules if we have a split.
-        if self.player_hands.1.len() > 0 {
-            // TODO: Add logic...
-        }
-        if self.player_total == self.dealer_total {
-            return (true, None);
-        }
-        if self.dealer_beats_player {
-            return (true, Some(Agent::Dealer)); 
-        }
-        if self.player_total == 21 {
-            return (true, Some(Agent::Player));
+// Deals rounds from a stacked shoe until too few cards are left for another
+// one, printing how each round was played.
+fn play_stack(opts: &Opt, stack: &str) -> eyre::Result<()> {
+    let shoe = Shoe::stacked(shoe::read_stack(stack)?, opts.counting_system);
+    let shoe = match opts.ace_points() {
+        Some(points) => shoe.with_ace_side_count(points),
+        None => shoe,
+    };
+    let mut cards = Arc::new(Mutex::new(shoe));
+    println!("*********************************************");
+    println!("* Stacked shoe                              *");
+    println!("*********************************************");
+    let mut round = 1;
+    while cards.lock().unwrap().remaining().len() >= 4 {
+        let mut game = Game::new(&mut cards)?.with_deviations(&opts.deviations);
+        game.start()
+            .wrap_err_with(|| format!("the stacked cards ran out during round {}", round))?;
+        let result = GameResult::from(game);
+        println!("Round {}:", round);
+        let hand = |cards: &[Card]| {
+            let names: Vec<String> = cards.iter().map(Card::to_string).collect();
+            format!("{} = {}", names.join(" "), hand_sum(cards))
+        };
+        println!("  Dealer hand: {}", hand(result.dealer_hand()));
+        println!("  Player hand: {}", hand(result.player_hands().0));
+        if !result.player_hands().1.is_empty() {
+            println!("  Split hand: {}", hand(result.player_hands().1));
         }
-        if self.dealer_total == 21 {
-            return (true, Some(Agent::Dealer));
+        for decision in result.decisions().iter() {
+            println!(
+                "  {} at TC {:+.2}: {:?}",
+                decision.situation, decision.true_count, decision.action,
+            );
         }
-        if self.player_total > 21 {
-            return (true, Some(Agent::Dealer)); 
+        if result.insured() {
+            println!("  Took insurance");
         }
-        if self.dealer_total > 21 {
-            return (true, Some(Agent::Player)); 
+        if !result.player_hands().1.is_empty() {
+            let [first, second] = result.hand_winners();
+            println!("  Hand winners: {:?}, {:?}", first, second);
         }
-        return (false, None);
+        println!("  Winner: {:?}, {:+.1} units", result.winner(), result.units());
+        round += 1;
     }
+    Ok(())
 }
 
-// Simple summary of the game for displaying to the user.
-pub struct GameResult {
-    _dealer_hand: Vec<Card>,
-    _player_hands: (Vec<Card>, Vec<Card>),
-    _player_moves: Vec<Move>,
-    winner: Option<Agent>,
-}
+// Replays every round of a hand history from its rebuilt shoe with the
+// configured strategy, and prints how many rounds came out the same along
+// with the ones that didn't.
+fn replay_history(opts: &Opt, path: &str) -> eyre::Result<()> {
+    // Rounds whose changes are printed in full.
+    const MAX_CHANGES_SHOWN: usize = 20;
+    let hands = history::read_history(path)?;
+    let replay = history::replay(
+        &hands,
+        &opts.shoe_config(),
+        &opts.deviations,
+        opts.replay_first_move.as_ref(),
+    )?;
 
-impl <'a, T> From<Game<'a, T>> for GameResult
-    where T: Iterator<Item=Card> {
-    fn from(g: Game<'a, T>) -> Self {
-        Self {
-            _dealer_hand: g.dealer_hand,
-            _player_hands: g.player_hands,
-            _player_moves: g.player_moves,
-            winner: g.winner,
-        } 
+    println!("*********************************************");
+    println!("* Replayed hand history                     *");
+    println!("*********************************************");
+    println!("History: {}", path);
+    println!("Rounds: {}", hands.len());
+    if replay.mismatched > 0 {
+        println!(
+            "Rounds whose shoe could not be rebuilt: {} (check -d, -p and --count match the recording)",
+            replay.mismatched,
+        );
+    }
+    println!("Rounds played out the same: {}", replay.same);
+    println!("Rounds played out differently: {}", replay.changes.len());
+    println!("Recorded net: {:.2} units", replay.recorded_net);
+    println!("Replayed net: {:.2} units ({:+.2})", replay.replayed_net, replay.replayed_net - replay.recorded_net);
+    for (hand, result) in replay.changes.iter().take(MAX_CHANGES_SHOWN) {
+        let moves = |decisions: &[Decision]| -> Vec<String> {
+            decisions
+                .iter()
+                .map(|d| format!("{} {:?}", d.situation, d.action))
+                .collect()
+        };
+        println!(
+            "  shoe {} shuffle {} card {}: recorded [{}] {:+.1}, replayed [{}] {:+.1}",
+            hand.shoe.seed,
+            hand.shoe.shuffle,
+            hand.shoe.position,
+            moves(&hand.decisions).join(", "),
+            hand.units,
+            moves(result.decisions()).join(", "),
+            result.units(),
+        );
+    }
+    if replay.changes.len() > MAX_CHANGES_SHOWN {
+        println!("  ... and {} more", replay.changes.len() - MAX_CHANGES_SHOWN);
     }
+    Ok(())
+}
+
+// Formats an estimate with the margin of its 95% confidence interval and its
+// standard error.
+fn estimate(value: f64, std_error: f64, precision: usize, unit: &str) -> String {
+    format!(
+        "{:.p$}{u} +/- {:.p$}{u} (SE {:.p$}{u})",
+        value,
+        stats::Z_95 * std_error,
+        std_error,
+        p = precision,
+        u = unit,
+    )
 }
 
-// Get the sum of cards in hand.
-pub fn hand_sum(hand: &Vec<Card>) -> u8 {
-    hand.into_iter().map(|c| u8::from(c)).sum()
+// Formats a count as a percentage of a total with its confidence interval.
+fn proportion(count: u64, total: u64) -> String {
+    let percent = count as f64 / total.max(1) as f64 * 100.0;
+    estimate(percent, stats::proportion_std_error(count, total) * 100.0, 2, "%")
 }
 
-// Take two cards from the deck iterator.
-pub fn take_two<T: Iterator<Item=Card>>(cards: &mut Arc<Mutex<T>>) -> Vec<Card> {
-    let binding = cards.clone();
-    let mut deck = binding.lock().unwrap();
-    vec![deck.next().unwrap(), deck.next().unwrap()]
+// Prints the money summary of a set of hands.
+fn print_tally(tally: &Tally) {
+    println!(
+        "Win rate per 100 hands: {}",
+        estimate(tally.win_rate_per_100(), tally.std_error() * 100.0, 2, " units"),
+    );
+    println!(
+        "Standard deviation per hand: {}",
+        estimate(tally.std_dev(), tally.std_dev_std_error(), 2, " units"),
+    );
+    println!(
+        "Player advantage: {}",
+        estimate(tally.advantage() * 100.0, tally.advantage_std_error() * 100.0, 2, "%"),
+    );
 }
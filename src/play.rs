@@ -0,0 +1,158 @@
+use std::io::{self, BufRead, Write};
+use std::sync::{Arc, Mutex};
+
+use blackjack_rs::rules::RULES;
+use blackjack_rs::shoe::Shoe;
+use blackjack_rs::{hand_sum, Card, Game, GameResult, Move, Player};
+
+use crate::Opt;
+
+// Shows cards by rank along with their total, e.g. "8 8 = 16".
+pub fn show(cards: &[Card]) -> String {
+    let names: Vec<String> = cards.iter().map(Card::to_string).collect();
+    format!("{} = {}", names.join(" "), hand_sum(cards))
+}
+
+// Prints a question and reads the answer, or None once input runs out.
+pub fn ask(question: &str) -> Option<String> {
+    print!("{}", question);
+    io::stdout().flush().ok()?;
+    let mut line = String::new();
+    match io::stdin().lock().read_line(&mut line) {
+        Ok(0) | Err(_) => None,
+        Ok(_) => Some(line.trim().to_lowercase()),
+    }
+}
+
+// How a move is offered, with the key that picks it in brackets.
+pub fn key(action: &Move) -> &'static str {
+    match action {
+        Move::Hit => "[h]it",
+        Move::Stand => "[s]tand",
+        Move::Double => "[d]ouble",
+        Move::Split => "s[p]lit",
+        Move::Surrender => "su[r]render",
+    }
+}
+
+// Reads a move from its key or its name.
+pub fn parse_move(answer: &str) -> Option<Move> {
+    match answer {
+        "h" => Some(Move::Hit),
+        "s" => Some(Move::Stand),
+        "d" => Some(Move::Double),
+        "p" => Some(Move::Split),
+        "r" => Some(Move::Surrender),
+        other => other.parse().ok(),
+    }
+}
+
+// A person at the terminal playing with a stack of chips. The game is told
+// about the stack, so moves it can't cover aren't among the legal ones.
+#[derive(Debug)]
+pub struct TerminalPlayer {
+    pub chips: f64,
+    pub bet: f64,
+    // Set once input runs out, after which every hand is stood on.
+    pub quit: bool,
+}
+
+impl Player for TerminalPlayer {
+    fn decide(&mut self, hand: &[Card], dealer_up_card: Card, legal: &[Move]) -> Move {
+        let keys: Vec<&str> = legal.iter().map(key).collect();
+        let question = format!(
+            "Your hand: {} against {}. {}? ",
+            show(hand),
+            dealer_up_card,
+            keys.join(", "),
+        );
+        loop {
+            let answer = match ask(&question) {
+                Some(answer) if !self.quit => answer,
+                _ => {
+                    self.quit = true;
+                    return Move::Stand;
+                }
+            };
+            match parse_move(&answer) {
+                Some(action) if legal.contains(&action) => return action,
+                _ => println!("Choose one of: {}", keys.join(", ")),
+            }
+        }
+    }
+    fn insure(&mut self, hand: &[Card]) -> bool {
+        if self.quit {
+            return false;
+        }
+        let question = format!("Your hand: {}. Dealer shows A, take insurance? [y/n] ", show(hand));
+        matches!(ask(&question).as_deref(), Some("y") | Some("yes"))
+    }
+}
+
+// Deals rounds to a person at the terminal until they quit or can't cover
+// the table minimum, settling each round against their chip stack.
+pub fn play(opts: &Opt, shoe: Shoe) -> eyre::Result<()> {
+    let mut cards = Arc::new(Mutex::new(shoe));
+    let mut player = TerminalPlayer {
+        chips: opts.bankroll,
+        bet: opts.table_min,
+        quit: false,
+    };
+    let mut rounds = 0;
+    println!(
+        "Table limits {}-{}, blackjack pays {} to 1, insurance pays {} to 1.",
+        opts.table_min, opts.table_max, RULES.blackjack_payout, RULES.insurance_payout,
+    );
+    while !player.quit && player.chips >= opts.table_min {
+        let question = format!(
+            "Chips: {}. Bet ({}-{}, enter for {}, q to quit): ",
+            player.chips,
+            opts.table_min,
+            opts.table_max.min(player.chips),
+            player.bet.min(player.chips),
+        );
+        let bet = match ask(&question).as_deref() {
+            None | Some("q") => break,
+            Some("") => player.bet.min(player.chips),
+            Some(amount) => match amount.parse::<f64>() {
+                Ok(bet) if bet >= opts.table_min && bet <= opts.table_max && bet <= player.chips => bet,
+                _ => {
+                    println!("Bet between {} and {}", opts.table_min, opts.table_max.min(player.chips));
+                    continue;
+                }
+            },
+        };
+        player.bet = bet;
+        if cards.lock().unwrap().shuffle_if_needed() {
+            println!("Shuffling.");
+        }
+        let mut game = Game::new(&mut cards)?;
+        println!("Dealer shows {}.", game.dealer_hand()[0]);
+        game = game.with_chips(player.chips, bet).with_player(&mut player);
+        game.start()?;
+        let result = GameResult::from(game).with_bet(bet, 0.0);
+        println!("Dealer hand: {}", show(result.dealer_hand()));
+        println!("Your hand: {}", show(result.player_hands().0));
+        if !result.player_hands().1.is_empty() {
+            println!("Your split hand: {}", show(result.player_hands().1));
+        }
+        let net = result.net();
+        player.chips += net;
+        let outcome = match net {
+            n if n > 0.0 => "You win",
+            n if n < 0.0 => "You lose",
+            _ => "Push",
+        };
+        println!("{} {:+}. Chips: {}", outcome, net, player.chips);
+        println!();
+        rounds += 1;
+    }
+    println!(
+        "Played {} round(s), {} chips to {} ({:+}).",
+        rounds,
+        opts.bankroll,
+        player.chips,
+        player.chips - opts.bankroll,
+    );
+    Ok(())
+}
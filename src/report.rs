@@ -0,0 +1,275 @@
+use std::io;
+use std::str::FromStr;
+
+use serde::Serialize;
+
+use blackjack_rs::rules::{Rules, RULES};
+use blackjack_rs::stats::{proportion_std_error, Stats, Tally};
+
+use crate::Opt;
+
+// How simulation results are written to stdout.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Format {
+    Text,
+    Json,
+    Csv,
+}
+
+impl FromStr for Format {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "text" => Ok(Format::Text),
+            "json" => Ok(Format::Json),
+            "csv" => Ok(Format::Csv),
+            _ => Err(format!("unknown format {}, expected text, json or csv", s)),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct Config {
+    pub seed: Option<u64>,
+    pub num_decks: usize,
+    // Cards of each kind in the shoe, when it isn't made of standard decks.
+    pub composition: Option<String>,
+    pub penetration: f64,
+    pub counting_system: String,
+    pub ace_side_count: Option<i32>,
+    pub units_per_true_count: f64,
+    pub min_bet: f64,
+    pub max_bet: f64,
+    pub table_min: f64,
+    pub table_max: f64,
+    pub wong_in: Option<f64>,
+    pub wong_out: f64,
+    pub deviations: usize,
+    pub rules: Rules,
+}
+
+// An estimate and its standard error.
+#[derive(Debug, Serialize)]
+pub struct Estimate {
+    pub value: f64,
+    pub std_error: f64,
+}
+
+impl Estimate {
+    fn proportion(count: u64, total: u64) -> Self {
+        Self {
+            value: count as f64 / total.max(1) as f64,
+            std_error: proportion_std_error(count, total),
+        }
+    }
+}
+
+// Money results of a set of hands, in units per hand.
+#[derive(Debug, Serialize)]
+pub struct Money {
+    pub hands: u64,
+    pub wagered: f64,
+    pub net: f64,
+    pub mean: Estimate,
+    pub std_dev: Estimate,
+    pub advantage: Estimate,
+}
+
+impl From<&Tally> for Money {
+    fn from(tally: &Tally) -> Self {
+        Self {
+            hands: tally.hands,
+            wagered: tally.wagered,
+            net: tally.net,
+            mean: Estimate {
+                value: tally.mean(),
+                std_error: tally.std_error(),
+            },
+            std_dev: Estimate {
+                value: tally.std_dev(),
+                std_error: tally.std_dev_std_error(),
+            },
+            advantage: Estimate {
+                value: tally.advantage(),
+                std_error: tally.advantage_std_error(),
+            },
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct Summary {
+    pub rounds_observed: u64,
+    pub games: u64,
+    pub player_wins: Estimate,
+    pub dealer_wins: Estimate,
+    pub ties: Estimate,
+    pub flat: Money,
+    pub spread: Money,
+    pub deviation_rounds: u64,
+    pub deviation_gain: Estimate,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TrueCountRow {
+    pub true_count: i32,
+    pub results: Money,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SituationRow {
+    pub player_hand: String,
+    pub dealer_up_card: u8,
+    pub player_wins: u64,
+    pub dealer_wins: u64,
+    pub ties: u64,
+    pub results: Money,
+}
+
+// Everything a run of the simulator reports, in a stable schema.
+#[derive(Debug, Serialize)]
+pub struct Report {
+    pub config: Config,
+    pub summary: Summary,
+    pub by_true_count: Vec<TrueCountRow>,
+    pub by_situation: Vec<SituationRow>,
+}
+
+impl Report {
+    pub fn new(opts: &Opt, stats: &Stats) -> Self {
+        let games = stats.games();
+        let config = Config {
+            seed: opts.seed,
+            num_decks: opts.num_decks,
+            composition: opts.composition.as_ref().map(|_| opts.composition().to_string()),
+            penetration: opts.penetration,
+            counting_system: opts.counting_system.to_string(),
+            ace_side_count: opts.ace_points(),
+            units_per_true_count: opts.units_per_true_count,
+            min_bet: opts.min_bet,
+            max_bet: opts.max_bet,
+            table_min: opts.table_min,
+            table_max: opts.table_max,
+            wong_in: opts.wong_in,
+            wong_out: opts.wong_out,
+            deviations: opts.deviations.plays.len(),
+            rules: RULES,
+        };
+        let summary = Summary {
+            rounds_observed: stats.rounds_observed,
+            games,
+            player_wins: Estimate::proportion(stats.player_wins, games),
+            dealer_wins: Estimate::proportion(stats.dealer_wins, games),
+            ties: Estimate::proportion(stats.ties, games),
+            flat: Money::from(&stats.flat),
+            spread: Money::from(&stats.spread),
+            deviation_rounds: stats.deviation_gain.hands,
+            deviation_gain: Estimate {
+                value: stats.deviation_gain.net / games.max(1) as f64,
+                std_error: stats.deviation_gain.std_error_over(games),
+            },
+        };
+        let by_true_count = stats
+            .by_true_count
+            .iter()
+            .map(|(true_count, tally)| TrueCountRow {
+                true_count: *true_count,
+                results: Money::from(tally),
+            })
+            .collect();
+        let by_situation = stats
+            .by_situation
+            .grid
+            .iter()
+            .map(|((hand, up_card), outcomes)| SituationRow {
+                player_hand: hand.to_string(),
+                dealer_up_card: *up_card,
+                player_wins: outcomes.player_wins,
+                dealer_wins: outcomes.dealer_wins,
+                ties: outcomes.ties,
+                results: Money::from(&outcomes.flat),
+            })
+            .collect();
+        Self {
+            config,
+            summary,
+            by_true_count,
+            by_situation,
+        }
+    }
+}
+
+// A row of the CSV output. The report is flattened into one long table so
+// every section shares the same columns: the section, the key of the row
+// within it (a true count or a situation), the metric and its value, with a
+// standard error where there is one.
+#[derive(Debug, Serialize)]
+struct CsvRow<'a> {
+    section: &'a str,
+    key: String,
+    metric: String,
+    value: String,
+    std_error: Option<f64>,
+}
+
+// Writes the report as pretty printed JSON.
+pub fn write_json<W: io::Write>(writer: W, report: &Report) -> eyre::Result<()> {
+    serde_json::to_writer_pretty(writer, report)?;
+    Ok(())
+}
+
+// Writes the report as a long CSV table. Nested values are named by joining
+// the path to them with dots, e.g. `flat.advantage`.
+pub fn write_csv<W: io::Write>(writer: W, report: &Report) -> eyre::Result<()> {
+    let mut csv = csv::Writer::from_writer(writer);
+    let mut write = |section: &str, key: String, value: serde_json::Value| -> eyre::Result<()> {
+        for (metric, value, std_error) in flatten("", value) {
+            csv.serialize(CsvRow {
+                section,
+                key: key.clone(),
+                metric,
+                value,
+                std_error,
+            })?;
+        }
+        Ok(())
+    };
+    write("config", String::new(), serde_json::to_value(&report.config)?)?;
+    write("summary", String::new(), serde_json::to_value(&report.summary)?)?;
+    for row in report.by_true_count.iter() {
+        write("true_count", row.true_count.to_string(), serde_json::to_value(&row.results)?)?;
+    }
+    for row in report.by_situation.iter() {
+        let key = format!("{} vs {}", row.player_hand, row.dealer_up_card);
+        let mut value = serde_json::to_value(&row.results)?;
+        value["player_wins"] = row.player_wins.into();
+        value["dealer_wins"] = row.dealer_wins.into();
+        value["ties"] = row.ties.into();
+        write("situation", key, value)?;
+    }
+    csv.flush()?;
+    Ok(())
+}
+
+// Flattens a JSON value into (metric, value, standard error) rows, folding
+// estimates into a single row.
+fn flatten(prefix: &str, value: serde_json::Value) -> Vec<(String, String, Option<f64>)> {
+    use serde_json::Value;
+    match value {
+        Value::Object(fields) => {
+            if let (Some(v), Some(Value::Number(se))) = (fields.get("value"), fields.get("std_error")) {
+                return vec![(prefix.to_string(), v.to_string(), se.as_f64())];
+            }
+            fields
+                .into_iter()
+                .flat_map(|(name, value)| {
+                    let path = if prefix.is_empty() { name } else { format!("{}.{}", prefix, name) };
+                    flatten(&path, value)
+                })
+                .collect()
+        }
+        Value::String(s) => vec![(prefix.to_string(), s, None)],
+        Value::Null => vec![(prefix.to_string(), String::new(), None)],
+        other => vec![(prefix.to_string(), other.to_string(), None)],
+    }
+}
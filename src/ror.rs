@@ -0,0 +1,56 @@
+// Closed form estimates for a player who wins `mean` units per hand on
+// average with a standard deviation of `sd` units per hand, treating the
+// bankroll as a random walk with drift.
+
+/// Probability of ever losing the whole bankroll when playing forever.
+pub fn risk_of_ruin(mean: f64, sd: f64, bankroll: f64) -> f64 {
+    if mean <= 0.0 {
+        return 1.0;
+    }
+    (-2.0 * mean * bankroll / (sd * sd)).exp().min(1.0)
+}
+
+/// Probability of losing the whole bankroll at some point within a number of
+/// hands.
+pub fn trip_risk_of_ruin(mean: f64, sd: f64, bankroll: f64, hands: f64) -> f64 {
+    if hands <= 0.0 || sd == 0.0 {
+        return 0.0;
+    }
+    let spread = sd * hands.sqrt();
+    let drift = mean * hands;
+    let ruined_by_end = normal_cdf((-bankroll - drift) / spread);
+    let crossed_before = (-2.0 * mean * bankroll / (sd * sd)).exp()
+        * normal_cdf((-bankroll + drift) / spread);
+    (ruined_by_end + crossed_before).min(1.0)
+}
+
+/// The bankroll needed to keep the risk of ruin down to a target probability.
+pub fn bankroll_for_risk(mean: f64, sd: f64, risk: f64) -> f64 {
+    if mean <= 0.0 {
+        return f64::INFINITY;
+    }
+    -(sd * sd) * risk.ln() / (2.0 * mean)
+}
+
+/// N0: the number of hands it takes for the expected win to equal one standard
+/// deviation of the results.
+pub fn n0(mean: f64, sd: f64) -> f64 {
+    if mean == 0.0 {
+        return f64::INFINITY;
+    }
+    (sd / mean).powi(2)
+}
+
+// Standard normal cumulative distribution, from the Abramowitz and Stegun
+// approximation of the error function.
+fn normal_cdf(x: f64) -> f64 {
+    let z = x.abs() / std::f64::consts::SQRT_2;
+    let t = 1.0 / (1.0 + 0.3275911 * z);
+    let poly = t * (0.254829592 + t * (-0.284496736 + t * (1.421413741 + t * (-1.453152027 + t * 1.061405429))));
+    let erf = 1.0 - poly * (-z * z).exp();
+    if x >= 0.0 {
+        0.5 * (1.0 + erf)
+    } else {
+        0.5 * (1.0 - erf)
+    }
+}
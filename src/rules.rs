@@ -0,0 +1,15 @@
+use serde::Serialize;
+
+/// The table rules the engine plays by. Only the payouts can differ between
+/// tables; everything else about how a round is dealt and played is fixed
+/// by the engine.
+#[derive(Debug, Serialize)]
+pub struct Rules {
+    pub blackjack_payout: f64,
+    pub insurance_payout: f64,
+}
+
+pub const RULES: Rules = Rules {
+    blackjack_payout: 1.5,
+    insurance_payout: 2.0,
+};
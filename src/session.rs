@@ -0,0 +1,101 @@
+use std::sync::{Arc, Mutex};
+
+use crate::bankroll::Bankroll;
+use crate::betting::BetSizing;
+use crate::deviations::Deviations;
+use crate::shoe::{CardSource, Shoe};
+use crate::systems::BettingSystem;
+use crate::{Game, GameError, GameResult};
+
+/// When a session stops: after losing or winning a given amount, or after a
+/// number of rounds. Amounts are in units.
+#[derive(Debug, Clone, Copy)]
+pub struct SessionLimits {
+    pub stop_loss: Option<f64>,
+    pub win_goal: Option<f64>,
+    pub rounds: u64,
+}
+
+/// Why a session ended.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SessionEnd {
+    StopLoss,
+    WinGoal,
+    Ruin,
+    RoundLimit,
+}
+
+/// The outcome of a single session.
+#[derive(Debug, Clone, Copy)]
+pub struct Session {
+    pub bankroll: Bankroll,
+    pub end: SessionEnd,
+    /// Rounds it took the bankroll to first reach twice its starting size.
+    pub rounds_to_double: Option<u64>,
+}
+
+impl Session {
+    /// Units won or lost over the session.
+    pub fn result(&self) -> f64 {
+        self.bankroll.balance - self.bankroll.start
+    }
+}
+
+/// Plays rounds from the shoe until one of the session limits is reached or
+/// the bankroll can no longer cover the table minimum.
+pub fn play_session(
+    cards: &mut Arc<Mutex<Shoe>>,
+    start: f64,
+    limits: &SessionLimits,
+    sizing: &BetSizing,
+    deviations: &Deviations,
+) -> Result<Session, GameError> {
+    let mut bankroll = Bankroll::new(start);
+    let mut rounds_to_double = None;
+    let mut progression = sizing.progression();
+    let mut previous: Option<GameResult> = None;
+    let end = loop {
+        if bankroll.is_ruined(sizing.table_min()) {
+            break SessionEnd::Ruin;
+        }
+        if limits.stop_loss.is_some_and(|loss| start - bankroll.balance >= loss) {
+            break SessionEnd::StopLoss;
+        }
+        if limits.win_goal.is_some_and(|goal| bankroll.balance - start >= goal) {
+            break SessionEnd::WinGoal;
+        }
+        if bankroll.rounds >= limits.rounds {
+            break SessionEnd::RoundLimit;
+        }
+        let true_count = {
+            let mut shoe = cards.lock().unwrap();
+            shoe.shuffle_if_needed();
+            shoe.true_count()
+        };
+        let bet = sizing.bet(
+            bankroll.balance,
+            true_count,
+            progression
+                .as_mut()
+                .map(|system| (system.as_mut() as &mut dyn BettingSystem, previous.as_ref())),
+        );
+        // The bet is at most the balance, and doubles, splits and insurance
+        // are only made while the balance covers them, so a round never
+        // loses more than the bankroll holds.
+        let mut game = Game::new(cards)?
+            .with_deviations(deviations)
+            .with_chips(bankroll.balance, bet);
+        game.start()?;
+        let result = GameResult::from(game).with_bet(bet, true_count);
+        bankroll.settle(bet * result.units_wagered(), result.net());
+        previous = Some(result);
+        if rounds_to_double.is_none() && bankroll.balance >= 2.0 * start {
+            rounds_to_double = Some(bankroll.rounds);
+        }
+    };
+    Ok(Session {
+        bankroll,
+        end,
+        rounds_to_double,
+    })
+}
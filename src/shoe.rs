@@ -0,0 +1,251 @@
+use std::fs;
+use std::path::Path;
+
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+use crate::composition::Composition;
+use crate::count::{Counter, CountingSystem};
+use crate::error::GameError;
+use crate::{Card, Deck};
+
+/// Anything a game can be dealt from. Sources keep a count of the cards they
+/// have dealt so the player's decisions can depend on it.
+pub trait CardSource: Iterator<Item = Card> {
+    /// Deals a card face down, which the counter doesn't see until it is
+    /// revealed, just as a player at the table wouldn't.
+    fn deal_face_down(&mut self) -> Option<Card>;
+    /// Shows the counter a card that was dealt face down.
+    fn reveal(&mut self, card: &Card);
+    fn counter(&self) -> &Counter;
+    fn true_count(&self) -> f64 {
+        self.counter().true_count()
+    }
+}
+
+/// A shoe of one or more decks that is dealt down to a cut card before being
+/// reshuffled. Every card that leaves the shoe is seen by its counter, face
+/// down cards once they are turned over.
+#[derive(Debug, Clone)]
+pub struct Shoe {
+    deck: Deck,
+    position: usize,
+    cut_card: usize,
+    counter: Counter,
+    // Cards dealt face down since the last shuffle that the counter hasn't
+    // seen yet.
+    face_down: usize,
+    // A stacked shoe deals exactly its cards and runs out rather than being
+    // reshuffled.
+    stacked: bool,
+    rng: StdRng,
+    // The seed the shoe was created with and how many times it has been
+    // reshuffled since, which together with the position identify a card.
+    seed: u64,
+    shuffles: u64,
+}
+
+impl Shoe {
+    /// Creates a shuffled shoe of the given cards. Penetration is the
+    /// fraction of the shoe dealt before the cut card comes out, e.g. 0.75 for
+    /// 4.5 of 6 decks. Shoes created with the same seed deal the same cards.
+    /// A shoe too small to deal a round, or with a penetration that isn't
+    /// above 0 and at most 1, is `GameError::InvalidRules`.
+    pub fn new(
+        composition: &Composition,
+        penetration: f64,
+        system: CountingSystem,
+        seed: u64,
+    ) -> Result<Self, GameError> {
+        check(composition, penetration)?;
+        let mut deck = composition.build();
+        let mut rng = StdRng::seed_from_u64(seed);
+        deck.shuffle(&mut rng);
+        let total = deck.cards.len();
+        let cut_card = ((total as f64) * penetration) as usize;
+        Ok(Self {
+            deck,
+            position: 0,
+            cut_card,
+            counter: Counter::new(system, total)
+                .with_initial_count(composition.initial_running_count(system))
+                .with_starting_counts(composition.rank_counts()),
+            face_down: 0,
+            stacked: false,
+            rng,
+            seed,
+            shuffles: 0,
+        })
+    }
+    /// A shoe that deals the given cards in order with no cut card. It is
+    /// never reshuffled, so a round that needs more cards than are left fails
+    /// with `GameError::ExhaustedShoe` instead of being dealt made up ones.
+    pub fn stacked(cards: Vec<Card>, system: CountingSystem) -> Self {
+        let total = cards.len();
+        Self {
+            deck: Deck { cards },
+            position: 0,
+            cut_card: total,
+            counter: Counter::new(system, total),
+            face_down: 0,
+            stacked: true,
+            rng: StdRng::seed_from_u64(0),
+            seed: 0,
+            shuffles: 0,
+        }
+    }
+    pub fn with_ace_side_count(mut self, points_per_ace: i32) -> Self {
+        self.counter = self.counter.with_ace_side_count(points_per_ace);
+        self
+    }
+    /// The cards left to be dealt, in the order they will come out.
+    pub fn remaining(&self) -> &[Card] {
+        &self.deck.cards[self.position..]
+    }
+    /// Cards dealt since the last shuffle.
+    pub fn position(&self) -> usize {
+        self.position
+    }
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+    pub fn shuffles(&self) -> u64 {
+        self.shuffles
+    }
+    /// The generator the shoe is shuffled with, for any other random draws
+    /// a simulation makes that should follow from the seed.
+    pub fn rng(&mut self) -> &mut StdRng {
+        &mut self.rng
+    }
+    /// Whether the cut card has come out and the shoe should be shuffled
+    /// before the next round.
+    pub fn needs_shuffle(&self) -> bool {
+        self.position >= self.cut_card
+    }
+    /// Shuffles the shoe if the cut card has come out, and says whether it
+    /// did.
+    pub fn shuffle_if_needed(&mut self) -> bool {
+        let needed = self.needs_shuffle();
+        if needed {
+            self.shuffle();
+        }
+        needed
+    }
+    pub fn shuffle(&mut self) {
+        self.deck.shuffle(&mut self.rng);
+        self.position = 0;
+        self.shuffles += 1;
+        self.face_down = 0;
+        self.counter.reset();
+    }
+    // Takes the next card off the shoe, reshuffling in the middle of a round
+    // if the shoe runs out completely, unless it is stacked.
+    fn draw(&mut self) -> Option<Card> {
+        if self.position >= self.deck.cards.len() && !self.stacked {
+            self.shuffle();
+        }
+        let card = *self.deck.cards.get(self.position)?;
+        self.position += 1;
+        Some(card)
+    }
+}
+
+/// How the shoes of a simulation are made up and counted, so every thread
+/// and batch can deal from its own shoe of the same kind.
+#[derive(Debug, Clone)]
+pub struct ShoeConfig {
+    pub composition: Composition,
+    pub penetration: f64,
+    pub system: CountingSystem,
+    /// Points per surplus ace when an ace side count is kept.
+    pub ace_side_count: Option<i32>,
+}
+
+impl ShoeConfig {
+    /// A shuffled shoe of this kind. Shoes with the same seed deal the same
+    /// cards.
+    pub fn shoe(&self, seed: u64) -> Result<Shoe, GameError> {
+        let shoe = Shoe::new(&self.composition, self.penetration, self.system, seed)?;
+        Ok(match self.ace_side_count {
+            Some(points) => shoe.with_ace_side_count(points),
+            None => shoe,
+        })
+    }
+    /// Checks shoes of this kind can be made, without shuffling one.
+    pub fn validate(&self) -> Result<(), GameError> {
+        check(&self.composition, self.penetration)
+    }
+}
+
+// A shoe needs the four cards of a round and a cut card somewhere in it.
+fn check(composition: &Composition, penetration: f64) -> Result<(), GameError> {
+    let cards = composition.total();
+    if cards < 4 {
+        return Err(GameError::InvalidRules(format!(
+            "the shoe holds {} cards, too few to deal a round",
+            cards,
+        )));
+    }
+    if !(penetration > 0.0 && penetration <= 1.0) {
+        return Err(GameError::InvalidRules(format!(
+            "penetration {} must be above 0 and at most 1",
+            penetration,
+        )));
+    }
+    Ok(())
+}
+
+impl CardSource for Shoe {
+    fn deal_face_down(&mut self) -> Option<Card> {
+        let card = self.draw()?;
+        self.face_down += 1;
+        Some(card)
+    }
+    /// A card dealt before the shoe was reshuffled went back into it, so it
+    /// is no longer counted when it is turned over.
+    fn reveal(&mut self, card: &Card) {
+        if self.face_down > 0 {
+            self.face_down -= 1;
+            self.counter.observe(card);
+        }
+    }
+    fn counter(&self) -> &Counter {
+        &self.counter
+    }
+}
+
+/// Deals cards face up from the shoe, where the counter sees them.
+impl Iterator for Shoe {
+    type Item = Card;
+    fn next(&mut self) -> Option<Card> {
+        let card = self.draw()?;
+        self.counter.observe(&card);
+        Some(card)
+    }
+}
+
+/// Parses cards separated by spaces or commas, e.g. "A T 8 8 6". Anything
+/// after a # on a line is a comment.
+pub fn parse_cards(s: &str) -> Result<Vec<Card>, String> {
+    s.lines()
+        .map(|line| line.split('#').next().unwrap_or_default())
+        .flat_map(|line| line.split(|c: char| c.is_whitespace() || c == ','))
+        .filter(|card| !card.is_empty())
+        .map(str::parse)
+        .collect()
+}
+
+/// Reads the cards of a stacked shoe from a file if one exists at the given
+/// path, or else from the string itself.
+pub fn read_stack(stack: &str) -> eyre::Result<Vec<Card>> {
+    let contents = if Path::new(stack).is_file() {
+        fs::read_to_string(stack)?
+    } else {
+        stack.to_string()
+    };
+    let cards = parse_cards(&contents).map_err(|e| eyre::eyre!(e))?;
+    if cards.len() < 4 {
+        return Err(eyre::eyre!("a stacked shoe needs at least 4 cards, got {}", cards.len()));
+    }
+    Ok(cards)
+}
@@ -0,0 +1,190 @@
+use std::panic;
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+use crate::betting::{BetRamp, BetSizing, Wong};
+use crate::deviations::Deviations;
+use crate::error::GameError;
+use crate::game::{Game, GameResult};
+use crate::history::{HandRecord, HistoryWriter, ShoePosition};
+use crate::session::{self, Session, SessionLimits};
+use crate::shoe::{CardSource, Shoe, ShoeConfig};
+use crate::stats::Stats;
+
+/// How far apart the seeds of consecutive batches are. Each thread seeds its
+/// shoe one past the last, so batches never deal from the same shoe.
+pub const BATCH_SEED_STEP: u64 = 1 << 32;
+
+/// A simulation run: how many rounds are dealt from what shoes, and how the
+/// player bets and plays them.
+#[derive(Debug, Clone)]
+pub struct Simulation {
+    pub shoes: ShoeConfig,
+    pub seed: u64,
+    pub rounds: usize,
+    pub ramp: BetRamp,
+    pub wong: Option<Wong>,
+    pub deviations: Deviations,
+}
+
+impl Simulation {
+    /// Splits a number of rounds (or sessions) across threads, each dealing
+    /// its share through its own shoe so the count it keeps reflects the
+    /// cards it has actually seen, and returns what each thread made of its
+    /// share.
+    pub fn run_threads<R, F>(&self, total: usize, play: F) -> Result<Vec<R>, GameError>
+    where
+        R: Send + 'static,
+        F: Fn(Shoe, usize) -> Result<R, GameError> + Send + Clone + 'static,
+    {
+        let num_threads = thread::available_parallelism()
+            .map(|n| n.get())
+            .unwrap_or(1)
+            .min(total.max(1));
+        let mut handlers = vec![];
+        for i in 0..num_threads {
+            let share = total / num_threads + usize::from(i < total % num_threads);
+            let shoe = self.shoes.shoe(self.seed.wrapping_add(i as u64))?;
+            let play = play.clone();
+            handlers.push(thread::spawn(move || play(shoe, share)));
+        }
+        handlers
+            .into_iter()
+            .map(|handler| handler.join().unwrap_or_else(|e| panic::resume_unwind(e)))
+            .collect()
+    }
+    /// Plays the rounds across threads, with each thread filling in its own
+    /// `R` from its shoe, and merges what the threads made of them into one.
+    pub fn run_merged<R, F, M>(&self, play: F, merge: M) -> Result<R, GameError>
+    where
+        R: Default + Send + 'static,
+        F: Fn(&mut R, Shoe, usize) -> Result<(), GameError> + Send + Clone + 'static,
+        M: Fn(&mut R, R),
+    {
+        let mut merged = R::default();
+        for r in self.run_threads(self.rounds, move |shoe, rounds| {
+            let mut r = R::default();
+            play(&mut r, shoe, rounds)?;
+            Ok(r)
+        })? {
+            merge(&mut merged, r);
+        }
+        Ok(merged)
+    }
+    /// Simulates the rounds, writing every hand played to the history if one
+    /// is given.
+    pub fn run(&self, history: Option<&HistoryWriter>) -> Result<Stats, GameError> {
+        let ramp = self.ramp;
+        let wong = self.wong;
+        let deviations = self.deviations.clone();
+        let history = history.cloned();
+        self.run_merged(
+            move |stats: &mut Stats, shoe, rounds| {
+                *stats = simulate_shoe(shoe, &ramp, wong.as_ref(), &deviations, history.as_ref(), rounds)?;
+                Ok(())
+            },
+            |stats, other| stats.merge(&other),
+        )
+    }
+    /// Keeps simulating batches of rounds, each dealt from freshly seeded
+    /// shoes, until the 95% confidence interval of the flat bet advantage is
+    /// narrower than the tolerance in percent, or until the maximum number of
+    /// rounds has been dealt.
+    pub fn run_until_converged(
+        &self,
+        tolerance: f64,
+        max_rounds: usize,
+        history: Option<&HistoryWriter>,
+    ) -> Result<Stats, GameError> {
+        let mut batch = self.clone();
+        let mut stats = Stats::default();
+        let mut dealt = 0;
+        loop {
+            batch.rounds = self.rounds.min(max_rounds - dealt);
+            stats.merge(&batch.run(history)?);
+            dealt += batch.rounds;
+            if stats.converged(tolerance) || dealt >= max_rounds {
+                return Ok(stats);
+            }
+            batch.next_batch();
+        }
+    }
+    /// Moves on to the shoes of the next batch.
+    pub fn next_batch(&mut self) {
+        self.seed = self.seed.wrapping_add(BATCH_SEED_STEP);
+    }
+    /// Plays a number of sessions from a starting bankroll. Each thread plays
+    /// its share of the sessions one after another through its own shoe.
+    pub fn sessions(
+        &self,
+        count: usize,
+        start: f64,
+        limits: SessionLimits,
+        sizing: BetSizing,
+    ) -> Result<Vec<Session>, GameError> {
+        let deviations = self.deviations.clone();
+        Ok(self
+            .run_threads(count, move |shoe, sessions| {
+                let mut cards = Arc::new(Mutex::new(shoe));
+                (0..sessions)
+                    .map(|_| session::play_session(&mut cards, start, &limits, &sizing, &deviations))
+                    .collect::<Result<Vec<Session>, GameError>>()
+            })?
+            .into_iter()
+            .flatten()
+            .collect())
+    }
+}
+
+/// Plays a number of consecutive rounds from a single shoe, sizing each bet
+/// off the true count before the round is dealt. Rounds in which a deviation
+/// was played are replayed with the same cards using basic strategy alone, so
+/// the gain from deviating is measured on exactly the hands it affected. When
+/// back-counting, rounds the player sits out are still dealt to keep the shoe
+/// moving, but only rounds they play are recorded.
+pub fn simulate_shoe(
+    shoe: Shoe,
+    ramp: &BetRamp,
+    wong: Option<&Wong>,
+    deviations: &Deviations,
+    history: Option<&HistoryWriter>,
+    rounds: usize,
+) -> Result<Stats, GameError> {
+    let mut cards = Arc::new(Mutex::new(shoe));
+    let mut stats = Stats::default();
+    let mut seated = false;
+    for _ in 0..rounds {
+        let (true_count, position) = {
+            let mut shoe = cards.lock().unwrap();
+            if shoe.shuffle_if_needed() {
+                seated = false;
+            }
+            (shoe.true_count(), ShoePosition::from(&*shoe))
+        };
+        stats.rounds_observed += 1;
+        seated = wong.is_none_or(|w| w.seated(seated, true_count));
+        if !seated {
+            Game::new(&mut cards)?.start()?;
+            continue;
+        }
+        // Only a round played with deviations can need replaying, so the
+        // shoe is only copied for one.
+        let before = (!deviations.is_empty()).then(|| cards.lock().unwrap().clone());
+        let mut game = Game::new(&mut cards)?.with_deviations(deviations);
+        game.start()?;
+        let result = GameResult::from(game).with_bet(ramp.bet(true_count), true_count);
+        if let Some(before) = before.filter(|_| result.deviated()) {
+            let mut replay_cards = Arc::new(Mutex::new(before));
+            let mut replay = Game::new(&mut replay_cards)?;
+            replay.start()?;
+            stats
+                .deviation_gain
+                .record(1.0, result.units() - GameResult::from(replay).units());
+        }
+        if let Some(history) = history {
+            history.write(&HandRecord::new(position, &result));
+        }
+        stats.record(&result);
+    }
+    Ok(stats)
+}
@@ -0,0 +1,135 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+
+use crate::stats::{Tally, Z_95};
+use crate::{hand_sum, Agent, Card, GameResult};
+
+/// Dealer up cards in the order they are shown, aces last.
+pub const DEALER_UP_CARDS: [u8; 10] = [2, 3, 4, 5, 6, 7, 8, 9, 10, 11];
+
+/// The two cards the player was dealt, grouped the way strategy tables group
+/// them: pairs, hands with an ace and everything else by total.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum StartingHand {
+    Hard(u8),
+    Soft(u8),
+    Pair(u8),
+}
+
+impl StartingHand {
+    pub fn new(cards: &[Card]) -> Self {
+        let total = hand_sum(cards);
+        if cards[0] == cards[1] {
+            StartingHand::Pair(u8::from(&cards[0]))
+        } else if cards.contains(&Card::A) {
+            StartingHand::Soft(total)
+        } else {
+            StartingHand::Hard(total)
+        }
+    }
+}
+
+impl fmt::Display for StartingHand {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            StartingHand::Hard(total) => write!(f, "hard {}", total),
+            StartingHand::Soft(total) => write!(f, "soft {}", total),
+            StartingHand::Pair(11) => write!(f, "pair A"),
+            StartingHand::Pair(value) => write!(f, "pair {}", value),
+        }
+    }
+}
+
+/// Outcomes of the rounds dealt in one situation, flat betting a unit.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Outcomes {
+    pub player_wins: u64,
+    pub dealer_wins: u64,
+    pub ties: u64,
+    pub flat: Tally,
+}
+
+/// Outcomes keyed by the player's starting hand and the dealer's up card.
+#[derive(Debug, Default, Clone)]
+pub struct Situations {
+    pub grid: BTreeMap<(StartingHand, u8), Outcomes>,
+}
+
+impl Situations {
+    pub fn record(&mut self, result: &GameResult) {
+        let outcomes = self
+            .grid
+            .entry((result.starting_hand(), result.dealer_up_card()))
+            .or_default();
+        match result.winner {
+            Some(Agent::Player) => outcomes.player_wins += 1,
+            Some(Agent::Dealer) => outcomes.dealer_wins += 1,
+            None => outcomes.ties += 1,
+        }
+        outcomes.flat.record(result.units_wagered(), result.units());
+    }
+    pub fn merge(&mut self, other: &Situations) {
+        for (key, theirs) in other.grid.iter() {
+            let ours = self.grid.entry(*key).or_default();
+            ours.player_wins += theirs.player_wins;
+            ours.dealer_wins += theirs.dealer_wins;
+            ours.ties += theirs.ties;
+            ours.flat.merge(&theirs.flat);
+        }
+    }
+    fn starting_hands(&self) -> Vec<StartingHand> {
+        let mut hands: Vec<StartingHand> = self.grid.keys().map(|(hand, _)| *hand).collect();
+        hands.dedup();
+        hands
+    }
+}
+
+pub fn up_card_name(up_card: u8) -> String {
+    match up_card {
+        11 => "A".to_string(),
+        value => value.to_string(),
+    }
+}
+
+/// Prints the units won per hand in each situation, in percent, with starting
+/// hands down the side and dealer up cards across the top.
+pub fn print_grid(situations: &Situations) {
+    print!("{:<9}", "Hand");
+    for up_card in DEALER_UP_CARDS {
+        print!("{:>8}", up_card_name(up_card));
+    }
+    println!();
+    for hand in situations.starting_hands() {
+        print!("{:<9}", hand.to_string());
+        for up_card in DEALER_UP_CARDS {
+            match situations.grid.get(&(hand, up_card)) {
+                Some(outcomes) => print!("{:>8.1}", outcomes.flat.mean() * 100.0),
+                None => print!("{:>8}", "-"),
+            }
+        }
+        println!();
+    }
+}
+
+/// Writes the outcomes of every situation as CSV.
+pub fn write_csv(path: &str, situations: &Situations) -> eyre::Result<()> {
+    let mut contents = String::from(
+        "player_hand,dealer_up_card,hands,player_wins,dealer_wins,ties,ev_percent,ci95_percent\n",
+    );
+    for ((hand, up_card), outcomes) in situations.grid.iter() {
+        contents.push_str(&format!(
+            "{},{},{},{},{},{},{:.4},{:.4}\n",
+            hand,
+            up_card_name(*up_card),
+            outcomes.flat.hands,
+            outcomes.player_wins,
+            outcomes.dealer_wins,
+            outcomes.ties,
+            outcomes.flat.mean() * 100.0,
+            Z_95 * outcomes.flat.std_error() * 100.0,
+        ));
+    }
+    fs::write(path, contents)?;
+    Ok(())
+}
@@ -0,0 +1,187 @@
+use std::collections::BTreeMap;
+
+use crate::situations::Situations;
+use crate::{Agent, GameResult};
+
+/// Multiple of the standard error on either side of an estimate that covers
+/// 95% of a normal distribution.
+pub const Z_95: f64 = 1.959964;
+
+/// Standard error of a proportion of a number of trials.
+pub fn proportion_std_error(count: u64, total: u64) -> f64 {
+    if total == 0 {
+        return 0.0;
+    }
+    let p = count as f64 / total as f64;
+    (p * (1.0 - p) / total as f64).sqrt()
+}
+
+/// Running totals of money won and lost over a number of hands, in units.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Tally {
+    pub hands: u64,
+    pub wagered: f64,
+    pub net: f64,
+    net_squared: f64,
+    wagered_squared: f64,
+    net_wagered: f64,
+}
+
+impl Tally {
+    pub fn record(&mut self, wagered: f64, net: f64) {
+        self.hands += 1;
+        self.wagered += wagered;
+        self.net += net;
+        self.net_squared += net * net;
+        self.wagered_squared += wagered * wagered;
+        self.net_wagered += net * wagered;
+    }
+    pub fn merge(&mut self, other: &Tally) {
+        self.hands += other.hands;
+        self.wagered += other.wagered;
+        self.net += other.net;
+        self.net_squared += other.net_squared;
+        self.wagered_squared += other.wagered_squared;
+        self.net_wagered += other.net_wagered;
+    }
+    /// Average units won per hand.
+    pub fn mean(&self) -> f64 {
+        if self.hands == 0 {
+            return 0.0;
+        }
+        self.net / self.hands as f64
+    }
+    pub fn win_rate_per_100(&self) -> f64 {
+        self.mean() * 100.0
+    }
+    /// Standard deviation of the result of a single hand, in units.
+    pub fn std_dev(&self) -> f64 {
+        if self.hands == 0 {
+            return 0.0;
+        }
+        let mean = self.mean();
+        (self.net_squared / self.hands as f64 - mean * mean).max(0.0).sqrt()
+    }
+    /// Standard error of the average units won per hand.
+    pub fn std_error(&self) -> f64 {
+        self.std_error_over(self.hands)
+    }
+    /// Standard error of the average units won per round, over a number of
+    /// rounds of which only the tallied hands won or lost anything.
+    pub fn std_error_over(&self, rounds: u64) -> f64 {
+        if rounds == 0 {
+            return 0.0;
+        }
+        let n = rounds as f64;
+        let mean = self.net / n;
+        ((self.net_squared / n - mean * mean).max(0.0) / n).sqrt()
+    }
+    /// Standard error of the standard deviation per hand, for roughly normal
+    /// results.
+    pub fn std_dev_std_error(&self) -> f64 {
+        if self.hands == 0 {
+            return 0.0;
+        }
+        self.std_dev() / (2.0 * self.hands as f64).sqrt()
+    }
+    /// Average units wagered per hand and its standard error.
+    pub fn mean_wager(&self) -> f64 {
+        if self.hands == 0 {
+            return 0.0;
+        }
+        self.wagered / self.hands as f64
+    }
+    pub fn mean_wager_std_error(&self) -> f64 {
+        if self.hands == 0 {
+            return 0.0;
+        }
+        let n = self.hands as f64;
+        let mean = self.mean_wager();
+        ((self.wagered_squared / n - mean * mean).max(0.0) / n).sqrt()
+    }
+    /// Units won per unit wagered, i.e. the player's edge over the house.
+    pub fn advantage(&self) -> f64 {
+        if self.wagered == 0.0 {
+            return 0.0;
+        }
+        self.net / self.wagered
+    }
+    /// Standard error of the advantage, a ratio of the units won to the units
+    /// wagered, from the spread of each hand's result around the advantage
+    /// times its wager.
+    pub fn advantage_std_error(&self) -> f64 {
+        if self.wagered == 0.0 {
+            return 0.0;
+        }
+        let n = self.hands as f64;
+        let a = self.advantage();
+        let residual = self.net_squared - 2.0 * a * self.net_wagered + a * a * self.wagered_squared;
+        (residual.max(0.0) / n).sqrt() / self.mean_wager() / n.sqrt()
+    }
+}
+
+/// Aggregated results of many simulated rounds. Every round is recorded both
+/// as if the player flat bet one unit, which is the basic strategy baseline,
+/// and with the wager chosen by the bet ramp.
+#[derive(Debug, Default, Clone)]
+pub struct Stats {
+    /// Rounds dealt, whether or not the player was seated for them.
+    pub rounds_observed: u64,
+    pub player_wins: u64,
+    pub dealer_wins: u64,
+    pub ties: u64,
+    pub flat: Tally,
+    pub spread: Tally,
+    /// Flat bet results keyed by the floored true count the round was dealt at.
+    pub by_true_count: BTreeMap<i32, Tally>,
+    /// Flat bet outcomes keyed by the starting hand and dealer up card.
+    pub by_situation: Situations,
+    /// Units won in each round in which an index play was used instead of
+    /// basic strategy over playing the same round by basic strategy alone.
+    pub deviation_gain: Tally,
+}
+
+impl Stats {
+    pub fn record(&mut self, result: &GameResult) {
+        match result.winner {
+            Some(Agent::Player) => self.player_wins += 1,
+            Some(Agent::Dealer) => self.dealer_wins += 1,
+            None => self.ties += 1,
+        }
+        let units = result.units();
+        self.flat.record(result.units_wagered(), units);
+        self.spread
+            .record(result.bet * result.units_wagered(), result.bet * units);
+        self.by_true_count
+            .entry(result.true_count.floor() as i32)
+            .or_default()
+            .record(result.units_wagered(), units);
+        self.by_situation.record(result);
+    }
+    pub fn merge(&mut self, other: &Stats) {
+        self.rounds_observed += other.rounds_observed;
+        self.player_wins += other.player_wins;
+        self.dealer_wins += other.dealer_wins;
+        self.ties += other.ties;
+        self.deviation_gain.merge(&other.deviation_gain);
+        self.flat.merge(&other.flat);
+        self.spread.merge(&other.spread);
+        for (tc, tally) in other.by_true_count.iter() {
+            self.by_true_count.entry(*tc).or_default().merge(tally);
+        }
+        self.by_situation.merge(&other.by_situation);
+    }
+    pub fn games(&self) -> u64 {
+        self.player_wins + self.dealer_wins + self.ties
+    }
+    /// Width of the 95% confidence interval of the flat bet advantage, in
+    /// percent.
+    pub fn edge_interval_width(&self) -> f64 {
+        2.0 * Z_95 * self.flat.advantage_std_error() * 100.0
+    }
+    /// Whether the interval is narrower than a tolerance in percent. With no
+    /// hands played it never will be, so that counts too.
+    pub fn converged(&self, tolerance: f64) -> bool {
+        self.games() == 0 || self.edge_interval_width() < tolerance
+    }
+}
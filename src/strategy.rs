@@ -1,12 +1,13 @@
 use std::collections::HashMap;
+use std::fs;
 use std::sync::{Arc,Mutex};
 use crate::Move;
 
 lazy_static! {
-    // Basic strategy maps player hand totals and the dealer up card
-    // to a move the player should make. The key is the player hand total
-    // and the dealer up card separated by a comma.
-    pub static ref BASIC_STRATEGY: Arc<Mutex<HashMap<&'static str, Move>>> = {
+    /// Basic strategy maps player hand totals and the dealer up card
+    /// to a move the player should make. The key is the player hand total
+    /// and the dealer up card separated by a comma.
+    pub static ref BASIC_STRATEGY: Arc<Mutex<HashMap<String, Move>>> = {
         let m = [
             // ************************
             // Hard strats! Dealer up 2.
@@ -252,7 +253,31 @@ lazy_static! {
             ("8,8,11", Move::Split),
             ("9,9,11", Move::Stand),
             ("10,10,11", Move::Stand),
-        ].iter().cloned().collect();
+        ].iter().map(|(key, action)| (key.to_string(), action.clone())).collect();
         Arc::new(Mutex::new(m))
     };
 }
+
+/// Loads a strategy chart over basic strategy, replacing the move for every
+/// situation it lists. Each line holds a situation, keyed the same as the
+/// basic strategy table, and a move, e.g. `16,10 Stand`. Blank lines and lines
+/// starting with # are ignored.
+pub fn load_chart(path: &str) -> eyre::Result<()> {
+    let contents = fs::read_to_string(path)?;
+    let mut strat = BASIC_STRATEGY.lock().unwrap();
+    for (i, line) in contents.lines().map(str::trim).enumerate() {
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let parts: Vec<&str> = line.split_whitespace().collect();
+        let (situation, action) = match parts[..] {
+            [situation, action] => (situation, action),
+            _ => return Err(eyre::eyre!("{} line {}: expected <situation> <move>", path, i + 1)),
+        };
+        let action: Move = action
+            .parse()
+            .map_err(|e| eyre::eyre!("{} line {}: {}", path, i + 1, e))?;
+        strat.insert(situation.to_string(), action);
+    }
+    Ok(())
+}
@@ -0,0 +1,254 @@
+use std::fmt;
+use std::str::FromStr;
+
+use crate::GameResult;
+
+/// A betting system picks the next wager, in units, from the outcome of the
+/// previous round, which was bet in units of the given size. None of them
+/// change the expectation of a single bet, only how much is bet and so how
+/// the results are spread out.
+pub trait BettingSystem {
+    fn next_bet(&mut self, previous: Option<&GameResult>, unit: f64) -> f64;
+}
+
+// Whether the previous round was won, lost or pushed, and by how many units.
+fn outcome(previous: Option<&GameResult>, unit: f64) -> Option<f64> {
+    previous.map(|r| r.net() / unit).filter(|net| *net != 0.0)
+}
+
+/// The classic negative and positive progressions.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum System {
+    Flat,
+    Martingale,
+    Paroli,
+    Fibonacci,
+    DAlembert,
+    Labouchere,
+    OscarsGrind,
+}
+
+pub const ALL_SYSTEMS: [System; 7] = [
+    System::Flat,
+    System::Martingale,
+    System::Paroli,
+    System::Fibonacci,
+    System::DAlembert,
+    System::Labouchere,
+    System::OscarsGrind,
+];
+
+impl System {
+    /// A fresh instance of the system, as at the start of a session.
+    pub fn start(&self) -> Box<dyn BettingSystem> {
+        match self {
+            System::Flat => Box::new(Flat),
+            System::Martingale => Box::new(Martingale { bet: 1.0 }),
+            System::Paroli => Box::new(Paroli { wins: 0 }),
+            System::Fibonacci => Box::new(Fibonacci { step: 0 }),
+            System::DAlembert => Box::new(DAlembert { bet: 1.0 }),
+            System::Labouchere => Box::new(Labouchere::new()),
+            System::OscarsGrind => Box::new(OscarsGrind { bet: 1.0, cycle: 0.0 }),
+        }
+    }
+}
+
+impl FromStr for System {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "flat" => Ok(System::Flat),
+            "martingale" => Ok(System::Martingale),
+            "paroli" => Ok(System::Paroli),
+            "fibonacci" => Ok(System::Fibonacci),
+            "dalembert" | "d'alembert" => Ok(System::DAlembert),
+            "labouchere" => Ok(System::Labouchere),
+            "oscar" | "oscars-grind" => Ok(System::OscarsGrind),
+            _ => Err(format!("unknown betting system {}", s)),
+        }
+    }
+}
+
+impl fmt::Display for System {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            System::Flat => "Flat",
+            System::Martingale => "Martingale",
+            System::Paroli => "Paroli",
+            System::Fibonacci => "Fibonacci",
+            System::DAlembert => "D'Alembert",
+            System::Labouchere => "Labouchere",
+            System::OscarsGrind => "Oscar's Grind",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// A list of systems to compare.
+#[derive(Debug, Clone)]
+pub struct Systems(pub Vec<System>);
+
+/// Parses a comma separated list of systems, or "all".
+impl FromStr for Systems {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s == "all" {
+            return Ok(Systems(ALL_SYSTEMS.to_vec()));
+        }
+        let systems = s
+            .split(',')
+            .map(|name| name.trim().parse())
+            .collect::<Result<Vec<System>, String>>()?;
+        Ok(Systems(systems))
+    }
+}
+
+// Always bets one unit.
+struct Flat;
+
+impl BettingSystem for Flat {
+    fn next_bet(&mut self, _previous: Option<&GameResult>, _unit: f64) -> f64 {
+        1.0
+    }
+}
+
+// Doubles the bet after every loss so the first win recovers all losses plus
+// one unit, and goes back to one unit after a win.
+struct Martingale {
+    bet: f64,
+}
+
+impl BettingSystem for Martingale {
+    fn next_bet(&mut self, previous: Option<&GameResult>, unit: f64) -> f64 {
+        match outcome(previous, unit) {
+            Some(net) if net < 0.0 => self.bet *= 2.0,
+            Some(_) => self.bet = 1.0,
+            None => {}
+        }
+        self.bet
+    }
+}
+
+// Doubles the bet after every win, up to three wins in a row, and goes back
+// to one unit after a loss or a completed run.
+struct Paroli {
+    wins: u32,
+}
+
+impl BettingSystem for Paroli {
+    fn next_bet(&mut self, previous: Option<&GameResult>, unit: f64) -> f64 {
+        match outcome(previous, unit) {
+            Some(net) if net > 0.0 => self.wins = (self.wins + 1) % 3,
+            Some(_) => self.wins = 0,
+            None => {}
+        }
+        2f64.powi(self.wins as i32)
+    }
+}
+
+// Moves one step up the Fibonacci sequence after a loss and two steps down
+// after a win.
+struct Fibonacci {
+    step: usize,
+}
+
+impl BettingSystem for Fibonacci {
+    fn next_bet(&mut self, previous: Option<&GameResult>, unit: f64) -> f64 {
+        match outcome(previous, unit) {
+            Some(net) if net < 0.0 => self.step += 1,
+            Some(_) => self.step = self.step.saturating_sub(2),
+            None => {}
+        }
+        let (mut a, mut b) = (1.0, 1.0);
+        for _ in 0..self.step {
+            (a, b) = (b, a + b);
+        }
+        a
+    }
+}
+
+// Adds a unit after a loss and takes one off after a win.
+struct DAlembert {
+    bet: f64,
+}
+
+impl BettingSystem for DAlembert {
+    fn next_bet(&mut self, previous: Option<&GameResult>, unit: f64) -> f64 {
+        match outcome(previous, unit) {
+            Some(net) if net < 0.0 => self.bet += 1.0,
+            Some(_) => self.bet = (self.bet - 1.0).max(1.0),
+            None => {}
+        }
+        self.bet
+    }
+}
+
+// Bets the sum of the first and last numbers of a line. A win crosses both
+// off and a loss adds the units lost to the end of the line. Clearing the
+// line wins its total, after which a new line is started.
+struct Labouchere {
+    line: Vec<f64>,
+}
+
+impl Labouchere {
+    fn new() -> Self {
+        Self {
+            line: vec![1.0, 2.0, 3.0, 4.0],
+        }
+    }
+    fn bet(&self) -> f64 {
+        match self.line.len() {
+            0 => 0.0,
+            1 => self.line[0],
+            n => self.line[0] + self.line[n - 1],
+        }
+    }
+}
+
+impl BettingSystem for Labouchere {
+    fn next_bet(&mut self, previous: Option<&GameResult>, unit: f64) -> f64 {
+        match outcome(previous, unit) {
+            Some(net) if net < 0.0 => self.line.push(-net),
+            Some(_) => {
+                self.line.pop();
+                if !self.line.is_empty() {
+                    self.line.remove(0);
+                }
+            }
+            None => {}
+        }
+        if self.line.is_empty() {
+            *self = Self::new();
+        }
+        self.bet()
+    }
+}
+
+// Aims to win one unit per cycle. The bet goes up a unit after a win, stays
+// the same after a loss, and is never more than needed to finish the cycle.
+struct OscarsGrind {
+    bet: f64,
+    cycle: f64,
+}
+
+impl BettingSystem for OscarsGrind {
+    fn next_bet(&mut self, previous: Option<&GameResult>, unit: f64) -> f64 {
+        if let Some(net) = outcome(previous, unit) {
+            self.cycle += net;
+            if self.cycle >= 1.0 {
+                self.cycle = 0.0;
+                self.bet = 1.0;
+            } else if net > 0.0 {
+                self.bet += 1.0;
+            }
+        }
+        // The last bet of a cycle is only what finishes it, which is kept
+        // as the bet from then on. Less than a unit can't be bet, so when
+        // the cycle is under a unit short the last bet is a whole unit.
+        let needed = 1.0 - self.cycle;
+        if self.bet > needed {
+            self.bet = needed.max(1.0);
+        }
+        self.bet
+    }
+}
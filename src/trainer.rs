@@ -0,0 +1,175 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+
+use blackjack_rs::situations::{up_card_name, StartingHand};
+use blackjack_rs::strategy::BASIC_STRATEGY;
+use blackjack_rs::{hand_sum, Game, Move};
+
+use crate::play::{ask, key, parse_move, show};
+use crate::Opt;
+
+// Situations players most often get wrong, keyed the same as the strategy
+// table. Soft hands and pairs count as tough too.
+const TOUGH_SITUATIONS: [&str; 14] = [
+    "16,10", "15,10", "16,9", "12,2", "12,3", "12,4", "13,2", "11,11", "10,10", "9,2", "9,7",
+    "8,8,10", "9,9,7", "4,4,5",
+];
+
+// Once a situation has been asked this many times, answering it correctly
+// less often than the threshold makes it tough for the player.
+const MIN_ASKED: u64 = 3;
+const WEAK_ACCURACY: f64 = 0.8;
+
+// With --train-tough, hands that aren't tough situations are only asked
+// this often; otherwise every hand is asked.
+const EASY_HAND_RATE: f64 = 0.25;
+
+#[derive(Debug, Default, Clone, Copy, Serialize, Deserialize)]
+pub struct Accuracy {
+    pub asked: u64,
+    pub correct: u64,
+}
+
+impl Accuracy {
+    pub fn rate(&self) -> f64 {
+        self.correct as f64 / self.asked.max(1) as f64
+    }
+}
+
+// How often each situation was answered correctly across training sessions,
+// kept in a JSON file.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct TrainerRecord {
+    pub situations: BTreeMap<String, Accuracy>,
+}
+
+impl TrainerRecord {
+    pub fn load(path: &str) -> eyre::Result<Self> {
+        if !Path::new(path).exists() {
+            return Ok(Self::default());
+        }
+        let contents = fs::read_to_string(path)?;
+        Ok(serde_json::from_str(&contents)?)
+    }
+    pub fn save(&self, path: &str) -> eyre::Result<()> {
+        fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+    fn is_weak(&self, situation: &str) -> bool {
+        self.situations
+            .get(situation)
+            .is_some_and(|a| a.asked >= MIN_ASKED && a.rate() < WEAK_ACCURACY)
+    }
+}
+
+// Deals hands from a shoe and asks for the move to make, checking each
+// answer against the strategy and keeping track of accuracy per situation.
+// With `tough` set, tough situations and the ones the player gets wrong are
+// asked more often than the rest.
+pub fn train(opts: &Opt, seed: u64, tough: bool, path: &str) -> eyre::Result<()> {
+    let mut record = TrainerRecord::load(path)?;
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut cards = Arc::new(Mutex::new(opts.shoe(seed)?));
+    let mut session = Accuracy::default();
+    println!("Basic strategy trainer. Answer with the key of a move, or q to quit.");
+    loop {
+        cards.lock().unwrap().shuffle_if_needed();
+        let game = Game::new(&mut cards)?;
+        let hand = game.player_hands().0.to_vec();
+        let correct = match game.strategy_move() {
+            Some(action) if hand_sum(&hand) < 21 => action,
+            _ => continue,
+        };
+        let starting_hand = StartingHand::new(&hand);
+        let key_in_chart = game.situation();
+        let situation = format!("{} vs {}", starting_hand, up_card_name(u8::from(&game.dealer_hand()[0])));
+        let is_tough = TOUGH_SITUATIONS.contains(&key_in_chart.as_str())
+            || !matches!(starting_hand, StartingHand::Hard(_))
+            || record.is_weak(&situation);
+        if !is_tough && !rng.gen_bool(if tough { EASY_HAND_RATE } else { 1.0 }) {
+            continue;
+        }
+        // The round isn't played, so every opening move is offered, with
+        // splitting only for a pair.
+        let mut legal = vec![Move::Hit, Move::Stand, Move::Double];
+        if game.has_pair() {
+            legal.push(Move::Split);
+        }
+        legal.push(Move::Surrender);
+        let keys: Vec<&str> = legal.iter().map(key).collect();
+        let question = format!(
+            "Dealer shows {}. Your hand: {} ({}). {}? ",
+            game.dealer_hand()[0],
+            show(&hand),
+            starting_hand,
+            keys.join(", "),
+        );
+        let answer = loop {
+            match ask(&question).as_deref() {
+                None | Some("q") => break None,
+                Some(answer) => match parse_move(answer) {
+                    Some(action) if legal.contains(&action) => break Some(action),
+                    _ => println!("Choose one of: {}", keys.join(", ")),
+                },
+            }
+        };
+        let answer = match answer {
+            Some(answer) => answer,
+            None => break,
+        };
+        let accuracy = record.situations.entry(situation.clone()).or_default();
+        accuracy.asked += 1;
+        session.asked += 1;
+        if answer == correct {
+            accuracy.correct += 1;
+            session.correct += 1;
+            println!("Correct.");
+        } else {
+            let reason = if BASIC_STRATEGY.lock().unwrap().contains_key(&key_in_chart) {
+                format!("strategy chart entry {}", key_in_chart)
+            } else if hand_sum(&hand) > 17 {
+                "always stand on 18 or more".to_string()
+            } else {
+                "always hit below 5".to_string()
+            };
+            println!("Wrong: {:?} on {} ({}).", correct, situation, reason);
+        }
+        println!(
+            "{}: {} of {} correct so far",
+            situation, accuracy.correct, accuracy.asked,
+        );
+        record.save(path)?;
+    }
+    println!();
+    println!(
+        "Session: {} of {} correct ({:.0}%)",
+        session.correct,
+        session.asked,
+        session.rate() * 100.0,
+    );
+    let mut weakest: Vec<(&String, &Accuracy)> = record
+        .situations
+        .iter()
+        .filter(|(_, a)| a.asked >= MIN_ASKED && a.correct < a.asked)
+        .collect();
+    weakest.sort_by(|a, b| a.1.rate().total_cmp(&b.1.rate()));
+    if !weakest.is_empty() {
+        println!("Situations to work on:");
+        for (situation, accuracy) in weakest.iter().take(5) {
+            println!(
+                "  {:<18} {} of {} correct ({:.0}%)",
+                situation,
+                accuracy.correct,
+                accuracy.asked,
+                accuracy.rate() * 100.0,
+            );
+        }
+    }
+    Ok(())
+}
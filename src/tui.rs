@@ -0,0 +1,510 @@
+use std::io;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Color, Style, Stylize};
+use ratatui::symbols::Marker;
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Axis, Bar, BarChart, BarGroup, Block, Chart, Dataset, Gauge, GraphType, Paragraph};
+use ratatui::{DefaultTerminal, Frame};
+
+use blackjack_rs::history::HistoryWriter;
+use blackjack_rs::shoe::Shoe;
+use blackjack_rs::stats::{self, Stats};
+use blackjack_rs::{hand_sum, Card, Game, GameResult, Move, Player};
+
+use crate::play::{key, parse_move};
+use crate::Opt;
+
+// Rounds dealt by each batch of a live simulation. The dashboard is updated
+// after every batch.
+const BATCH_ROUNDS: usize = 20_000;
+
+// True counts dealt less often than this share of hands are left out of the
+// count distribution.
+const MIN_TRUE_COUNT_SHARE: f64 = 0.001;
+
+// Waits for a key to be pressed.
+fn read_key() -> io::Result<KeyCode> {
+    loop {
+        if let Event::Key(key) = event::read()? {
+            if key.kind == KeyEventKind::Press {
+                return Ok(key.code);
+            }
+        }
+    }
+}
+
+// Cards drawn as white tiles, e.g. [ A ] [ 7 ], with a blank tile for a hole
+// card that hasn't been turned over.
+fn tiles(cards: &[Card], hole_card: bool) -> Line<'static> {
+    let tile = Style::default().fg(Color::Black).bg(Color::White);
+    let mut spans: Vec<Span> = cards
+        .iter()
+        .flat_map(|card| [Span::styled(format!(" {} ", card), tile), Span::raw(" ")])
+        .collect();
+    if hole_card {
+        spans.push(Span::styled(" ? ", Style::default().fg(Color::White).bg(Color::Blue)));
+    } else if !cards.is_empty() {
+        spans.push(Span::raw(format!("= {}", hand_sum(cards))));
+    }
+    Line::from(spans)
+}
+
+// What the table shows: the cards dealt, the chips and what the player is
+// asked to do next.
+#[derive(Debug, Default)]
+struct Table {
+    dealer: Vec<Card>,
+    hole_card: bool,
+    hand: Vec<Card>,
+    split_hand: Vec<Card>,
+    message: String,
+    prompt: String,
+}
+
+// A person playing at a table drawn in the terminal. Like the line based
+// player, the game is told about the stack so moves it can't cover aren't
+// offered.
+#[derive(Debug)]
+pub struct TuiPlayer {
+    terminal: DefaultTerminal,
+    table: Table,
+    chips: f64,
+    bet: f64,
+    table_min: f64,
+    table_max: f64,
+    // Set if the terminal stops responding, after which every hand is stood
+    // on.
+    quit: bool,
+}
+
+impl TuiPlayer {
+    fn draw(&mut self) -> io::Result<()> {
+        let (table, chips, bet) = (&self.table, self.chips, self.bet);
+        let title = format!(" Blackjack, table limits {}-{} ", self.table_min, self.table_max);
+        self.terminal.draw(|frame| render_table(frame, &title, table, chips, bet))?;
+        Ok(())
+    }
+    // Shows the prompt and waits for one of the moves offered.
+    fn choose(&mut self, offered: &[Move]) -> io::Result<Move> {
+        let keys: Vec<&str> = offered.iter().map(key).collect();
+        self.table.prompt = keys.join("  ");
+        loop {
+            self.draw()?;
+            if let KeyCode::Char(c) = read_key()? {
+                match parse_move(&c.to_string()) {
+                    Some(action) if offered.contains(&action) => return Ok(action),
+                    _ => self.table.message = format!("Choose one of: {}", keys.join(", ")),
+                }
+            }
+        }
+    }
+    // Shows the bet and lets the player raise or lower it before dealing.
+    // Returns None when they leave the table.
+    fn place_bet(&mut self) -> io::Result<Option<f64>> {
+        let most = self.table_max.min(self.chips);
+        self.bet = self.bet.clamp(self.table_min, most);
+        loop {
+            self.table.prompt = format!(
+                "Bet {}  [←/-] lower  [→/+] raise  [enter] deal  [q] leave",
+                self.bet,
+            );
+            self.draw()?;
+            match read_key()? {
+                KeyCode::Left | KeyCode::Char('-') => {
+                    self.bet = (self.bet - self.table_min).max(self.table_min);
+                }
+                KeyCode::Right | KeyCode::Char('+') => {
+                    self.bet = (self.bet + self.table_min).min(most);
+                }
+                KeyCode::Enter => return Ok(Some(self.bet)),
+                KeyCode::Char('q') | KeyCode::Esc => return Ok(None),
+                _ => {}
+            }
+        }
+    }
+}
+
+impl Player for TuiPlayer {
+    fn decide(&mut self, hand: &[Card], _dealer_up_card: Card, legal: &[Move]) -> Move {
+        if self.quit {
+            return Move::Stand;
+        }
+        self.table.hand = hand.to_vec();
+        match self.choose(legal) {
+            Ok(action) => {
+                self.table.message.clear();
+                action
+            }
+            Err(_) => {
+                self.quit = true;
+                Move::Stand
+            }
+        }
+    }
+    fn insure(&mut self, hand: &[Card]) -> bool {
+        if self.quit {
+            return false;
+        }
+        self.table.hand = hand.to_vec();
+        self.table.prompt = "Insurance?  [y]es  [n]o".to_string();
+        loop {
+            if self.draw().is_err() {
+                self.quit = true;
+                return false;
+            }
+            match read_key() {
+                Ok(KeyCode::Char('y')) => return true,
+                Ok(KeyCode::Char('n')) => return false,
+                Ok(_) => {}
+                Err(_) => {
+                    self.quit = true;
+                    return false;
+                }
+            }
+        }
+    }
+}
+
+fn render_table(frame: &mut Frame, title: &str, table: &Table, chips: f64, bet: f64) {
+    let block = Block::bordered().title(title.to_string().bold());
+    let area = block.inner(frame.area());
+    frame.render_widget(block, frame.area());
+    let [dealer, hands, status, message, prompt] = Layout::vertical([
+        Constraint::Length(3),
+        Constraint::Length(4),
+        Constraint::Length(2),
+        Constraint::Min(1),
+        Constraint::Length(1),
+    ])
+    .areas(area);
+    frame.render_widget(
+        Paragraph::new(vec![Line::from("Dealer".bold()), tiles(&table.dealer, table.hole_card)]),
+        dealer,
+    );
+    let mut lines = vec![Line::from("You".bold()), tiles(&table.hand, false)];
+    if !table.split_hand.is_empty() {
+        lines.push(tiles(&table.split_hand, false));
+    }
+    frame.render_widget(Paragraph::new(lines), hands);
+    frame.render_widget(
+        Paragraph::new(format!("Chips: {}   Bet: {}", chips, bet)).yellow(),
+        status,
+    );
+    frame.render_widget(Paragraph::new(table.message.as_str()), message);
+    frame.render_widget(Paragraph::new(table.prompt.as_str()).cyan(), prompt);
+}
+
+// Deals rounds at a table drawn in the terminal until the player leaves or
+// can't cover the table minimum, the same way as playing at the prompt.
+pub fn play(opts: &Opt, shoe: Shoe) -> eyre::Result<()> {
+    let mut player = TuiPlayer {
+        terminal: ratatui::try_init()?,
+        table: Table::default(),
+        chips: opts.bankroll,
+        bet: opts.table_min,
+        table_min: opts.table_min,
+        table_max: opts.table_max,
+        quit: false,
+    };
+    let played = play_rounds(shoe, &mut player);
+    ratatui::restore();
+    let rounds = played?;
+    println!(
+        "Played {} round(s), {} chips to {} ({:+}).",
+        rounds,
+        opts.bankroll,
+        player.chips,
+        player.chips - opts.bankroll,
+    );
+    Ok(())
+}
+
+fn play_rounds(shoe: Shoe, player: &mut TuiPlayer) -> eyre::Result<usize> {
+    let mut cards = Arc::new(Mutex::new(shoe));
+    let mut rounds = 0;
+    while !player.quit && player.chips >= player.table_min {
+        let bet = match player.place_bet()? {
+            Some(bet) => bet,
+            None => break,
+        };
+        cards.lock().unwrap().shuffle_if_needed();
+        let mut game = Game::new(&mut cards)?;
+        player.table = Table {
+            dealer: vec![game.dealer_hand()[0]],
+            hole_card: true,
+            ..Table::default()
+        };
+        game = game.with_chips(player.chips, bet).with_player(player);
+        game.start()?;
+        let result = GameResult::from(game).with_bet(bet, 0.0);
+        let net = result.net();
+        player.chips += net;
+        let outcome = match net {
+            n if n > 0.0 => "You win",
+            n if n < 0.0 => "You lose",
+            _ => "Push",
+        };
+        player.table = Table {
+            dealer: result.dealer_hand().to_vec(),
+            hole_card: false,
+            hand: result.player_hands().0.to_vec(),
+            split_hand: result.player_hands().1.to_vec(),
+            message: format!("{} {:+}.", outcome, net),
+            prompt: String::new(),
+        };
+        rounds += 1;
+    }
+    Ok(rounds)
+}
+
+// The flat bet house edge after some number of rounds, with the bounds of its
+// 95% confidence interval, all in percent.
+#[derive(Debug, Clone, Copy)]
+struct EdgePoint {
+    rounds: f64,
+    edge: f64,
+    low: f64,
+    high: f64,
+}
+
+impl EdgePoint {
+    fn new(stats: &Stats) -> Self {
+        let edge = -stats.flat.advantage() * 100.0;
+        let margin = stats::Z_95 * stats.flat.advantage_std_error() * 100.0;
+        Self {
+            rounds: stats.rounds_observed as f64,
+            edge,
+            low: edge - margin,
+            high: edge + margin,
+        }
+    }
+}
+
+// Simulates in batches while a dashboard shows the house edge converging,
+// how fast rounds are dealt and how often each true count comes up. The
+// simulation stops after the configured number of games, or once the house
+// edge is within --tolerance or --max-rounds have been dealt, or when q is
+// pressed, and returns what was simulated by then.
+pub fn dashboard(opts: &Opt, history: Option<&HistoryWriter>) -> eyre::Result<Stats> {
+    let stop = Arc::new(AtomicBool::new(false));
+    let (sender, receiver) = mpsc::channel();
+    let worker = {
+        let stop = stop.clone();
+        let mut batch = opts.simulation();
+        let history = history.cloned();
+        let tolerance = opts.tolerance;
+        let total = match tolerance {
+            Some(_) => opts.max_rounds,
+            None => opts.simulation_count,
+        };
+        thread::spawn(move || -> eyre::Result<Stats> {
+            let mut stats = Stats::default();
+            let mut dealt = 0;
+            while !stop.load(Ordering::Relaxed) {
+                batch.rounds = BATCH_ROUNDS.min(total - dealt);
+                stats.merge(&batch.run(history.as_ref())?);
+                dealt += batch.rounds;
+                batch.next_batch();
+                let done = dealt >= total || tolerance.is_some_and(|t| stats.converged(t));
+                if sender.send((stats.clone(), done)).is_err() || done {
+                    break;
+                }
+            }
+            Ok(stats)
+        })
+    };
+    let mut terminal = ratatui::try_init()?;
+    let shown = watch(&mut terminal, opts, &receiver, &stop);
+    ratatui::restore();
+    stop.store(true, Ordering::Relaxed);
+    let stats = worker.join().map_err(|_| eyre::eyre!("the simulation panicked"))?;
+    shown?;
+    stats
+}
+
+// Redraws the dashboard as batches come in until the simulation is done and
+// a key is pressed, or q is pressed to stop it early.
+fn watch(
+    terminal: &mut DefaultTerminal,
+    opts: &Opt,
+    receiver: &mpsc::Receiver<(Stats, bool)>,
+    stop: &AtomicBool,
+) -> io::Result<()> {
+    let started = Instant::now();
+    let mut stats = Stats::default();
+    let mut points = vec![];
+    let mut elapsed = Duration::ZERO;
+    let mut done = false;
+    loop {
+        loop {
+            match receiver.try_recv() {
+                Ok((latest, finished)) => {
+                    points.push(EdgePoint::new(&latest));
+                    stats = latest;
+                    done = finished;
+                }
+                // The simulation stopped on an error, which is reported once
+                // the dashboard is closed.
+                Err(mpsc::TryRecvError::Disconnected) => {
+                    done = true;
+                    break;
+                }
+                Err(mpsc::TryRecvError::Empty) => break,
+            }
+        }
+        if !done {
+            elapsed = started.elapsed();
+        }
+        terminal.draw(|frame| render_dashboard(frame, opts, &stats, &points, elapsed, done))?;
+        if event::poll(Duration::from_millis(100))? {
+            if let Event::Key(key) = event::read()? {
+                let quit = matches!(key.code, KeyCode::Char('q') | KeyCode::Esc);
+                if key.kind == KeyEventKind::Press && (done || quit) {
+                    stop.store(true, Ordering::Relaxed);
+                    return Ok(());
+                }
+            }
+        }
+    }
+}
+
+fn render_dashboard(
+    frame: &mut Frame,
+    opts: &Opt,
+    stats: &Stats,
+    points: &[EdgePoint],
+    elapsed: Duration,
+    done: bool,
+) {
+    let block = Block::bordered().title(" Blackjack simulation ".bold());
+    let area = block.inner(frame.area());
+    frame.render_widget(block, frame.area());
+    let [progress, summary, charts, help_area] = Layout::vertical([
+        Constraint::Length(1),
+        Constraint::Length(5),
+        Constraint::Min(10),
+        Constraint::Length(1),
+    ])
+    .areas(area);
+    let [edge_chart, count_chart] =
+        Layout::horizontal([Constraint::Percentage(60), Constraint::Percentage(40)]).areas(charts);
+
+    let edge = points.last().copied().unwrap_or(EdgePoint {
+        rounds: 0.0,
+        edge: 0.0,
+        low: 0.0,
+        high: 0.0,
+    });
+    let (ratio, label) = match opts.tolerance {
+        Some(tolerance) => {
+            let width = edge.high - edge.low;
+            let ratio = if width > 0.0 { (tolerance / width).min(1.0) } else { 0.0 };
+            (ratio, format!("confidence interval {:.3}% wide, target {}%", width, tolerance))
+        }
+        None => {
+            let ratio = stats.rounds_observed as f64 / opts.simulation_count.max(1) as f64;
+            (ratio.min(1.0), format!("{} of {} rounds", stats.rounds_observed, opts.simulation_count))
+        }
+    };
+    frame.render_widget(Gauge::default().ratio(ratio).label(label).green(), progress);
+
+    // Rounds sat out while back-counting are dealt but not played, so only
+    // the hands played count towards the throughput.
+    let per_second = stats.games() as f64 / elapsed.as_secs_f64().max(0.001);
+    frame.render_widget(
+        Paragraph::new(vec![
+            Line::from(format!(
+                "Rounds: {}   Hands played: {}   Throughput: {:.0} hands/sec",
+                stats.rounds_observed,
+                stats.games(),
+                per_second,
+            )),
+            Line::from(format!(
+                "House edge, flat bet: {:.3}% (95% CI {:.3}% to {:.3}%)",
+                edge.edge, edge.low, edge.high,
+            )),
+            Line::from(format!(
+                "Player advantage with bet ramp: {:.3}% +/- {:.3}%",
+                stats.spread.advantage() * 100.0,
+                stats::Z_95 * stats.spread.advantage_std_error() * 100.0,
+            )),
+            Line::from(format!(
+                "Win rate per 100 hands: {:.2} units, standard deviation {:.2} units",
+                stats.spread.win_rate_per_100(),
+                stats.spread.std_dev(),
+            )),
+        ]),
+        summary,
+    );
+
+    // Leave out the first batch's interval, which is wide enough to flatten
+    // the rest of the chart.
+    let shown = if points.len() > 1 { &points[1..] } else { points };
+    let line = |f: fn(&EdgePoint) -> f64| -> Vec<(f64, f64)> { shown.iter().map(|p| (p.rounds, f(p))).collect() };
+    let (edges, lows, highs) = (line(|p| p.edge), line(|p| p.low), line(|p| p.high));
+    let low = lows.iter().map(|p| p.1).fold(f64::INFINITY, f64::min);
+    let high = highs.iter().map(|p| p.1).fold(f64::NEG_INFINITY, f64::max);
+    let (low, high) = if low < high { (low, high) } else { (edge.edge - 1.0, edge.edge + 1.0) };
+    let rounds = shown.last().map_or(1.0, |p| p.rounds);
+    let band = Style::default().fg(Color::DarkGray);
+    let datasets = vec![
+        Dataset::default().marker(Marker::Braille).graph_type(GraphType::Line).style(band).data(&lows),
+        Dataset::default().marker(Marker::Braille).graph_type(GraphType::Line).style(band).data(&highs),
+        Dataset::default()
+            .name("house edge %")
+            .marker(Marker::Braille)
+            .graph_type(GraphType::Line)
+            .yellow()
+            .data(&edges),
+    ];
+    frame.render_widget(
+        Chart::new(datasets)
+            .block(Block::bordered().title(" House edge and 95% confidence band "))
+            .x_axis(
+                Axis::default()
+                    .bounds([shown.first().map_or(0.0, |p| p.rounds), rounds])
+                    .labels(["".to_string(), format!("{} rounds", rounds)]),
+            )
+            .y_axis(
+                Axis::default()
+                    .bounds([low, high])
+                    .labels([format!("{:.2}%", low), format!("{:.2}%", high)]),
+            ),
+        edge_chart,
+    );
+
+    let hands = stats.games().max(1) as f64;
+    let bars: Vec<Bar> = stats
+        .by_true_count
+        .iter()
+        .filter(|(_, tally)| tally.hands as f64 / hands >= MIN_TRUE_COUNT_SHARE)
+        .map(|(true_count, tally)| {
+            Bar::default()
+                .value(tally.hands)
+                .text_value(format!("{:.0}", tally.hands as f64 / hands * 100.0))
+                .label(Line::from(true_count.to_string()))
+        })
+        .collect();
+    frame.render_widget(
+        BarChart::default()
+            .block(Block::bordered().title(" Hands by true count, % "))
+            .data(BarGroup::default().bars(&bars))
+            .bar_width(3)
+            .bar_gap(1)
+            .cyan(),
+        count_chart,
+    );
+
+    let help = if done {
+        "Done. Press any key for the full report."
+    } else {
+        "[q] stop early and show the report"
+    };
+    frame.render_widget(Paragraph::new(help).dim(), help_area);
+}
@@ -0,0 +1,65 @@
+use blackjack_rs::composition::{Change, Changes, Composition};
+use blackjack_rs::count::{Counter, CountingSystem};
+use blackjack_rs::Card;
+
+#[test]
+fn changes_parse_set_add_and_remove() {
+    let changes: Changes = "5=0, T+8 A-2 K".parse().unwrap();
+    assert_eq!(
+        changes.0,
+        [
+            Change::Set(Card::Five, 0),
+            Change::Add(Card::Ten, 8),
+            Change::Remove(Card::A, 2),
+            Change::Remove(Card::K, 1),
+        ],
+    );
+}
+
+#[test]
+fn changes_reject_bad_counts_and_cards() {
+    assert!("5=x".parse::<Changes>().is_err());
+    assert!("T+-1".parse::<Changes>().is_err());
+    assert!("Z".parse::<Changes>().is_err());
+}
+
+#[test]
+fn changes_apply_to_standard_decks() {
+    let changes: Changes = "5=0 T+8 A-2".parse().unwrap();
+    let composition = Composition::standard(1).apply(&changes);
+    assert_eq!(composition.total(), 52 - 4 + 8 - 2);
+    // Twos through nines, ten-valued cards and aces.
+    assert_eq!(composition.rank_counts(), [4, 4, 4, 0, 4, 4, 4, 4, 24, 2]);
+}
+
+#[test]
+fn removing_more_cards_than_there_are_leaves_none() {
+    let composition = Composition::standard(1).remove(Card::A, 10);
+    assert_eq!(composition.rank_counts()[9], 0);
+}
+
+#[test]
+fn missing_cards_start_the_count_as_if_seen() {
+    // Four fives gone is +4 in Hi-Lo, and two extra aces, as if two seen
+    // aces had been put back, another +2.
+    let changes: Changes = "5=0 A+2".parse().unwrap();
+    let composition = Composition::standard(1).apply(&changes);
+    assert_eq!(composition.initial_running_count(CountingSystem::HiLo), 4 + 2);
+}
+
+#[test]
+fn surplus_aces_are_measured_against_the_starting_cards() {
+    // Two aces added to a single deck leave two more than standard decks
+    // would hold among the 54 cards, less the share of an ace they'd hold
+    // in the two extra cards.
+    let composition = Composition::standard(1).add(Card::A, 2);
+    let counter = Counter::new(CountingSystem::HiLo, composition.total())
+        .with_starting_counts(composition.rank_counts());
+    let expected = 6.0 - 54.0 * 4.0 / 52.0;
+    assert!((counter.surplus_aces() - expected).abs() < 1e-9);
+
+    let mut standard = Counter::new(CountingSystem::HiLo, 52);
+    assert_eq!(standard.surplus_aces(), 0.0);
+    standard.observe(&Card::A);
+    assert!(standard.surplus_aces() < 0.0);
+}
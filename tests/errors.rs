@@ -0,0 +1,60 @@
+use std::sync::{Arc, Mutex};
+
+use blackjack_rs::betting::BetRamp;
+use blackjack_rs::count::CountingSystem;
+use blackjack_rs::shoe::{parse_cards, Shoe};
+use blackjack_rs::strategy::BASIC_STRATEGY;
+use blackjack_rs::{Card, Deck, Game, GameError};
+
+fn ramp() -> BetRamp {
+    BetRamp {
+        units_per_true_count: 1.0,
+        min_units: 1.0,
+        max_units: 8.0,
+        table_min: 1.0,
+        table_max: 100.0,
+    }
+}
+
+#[test]
+fn a_shoe_that_cant_be_dealt_from_is_invalid() {
+    let too_few = Deck::builder(0).add(Card::Ten, 3);
+    assert!(matches!(
+        Shoe::new(&too_few, 0.75, CountingSystem::HiLo, 7),
+        Err(GameError::InvalidRules(_)),
+    ));
+    for penetration in [0.0, 1.5, f64::NAN] {
+        assert!(matches!(
+            Shoe::new(&Deck::builder(6), penetration, CountingSystem::HiLo, 7),
+            Err(GameError::InvalidRules(_)),
+        ));
+    }
+    assert!(Shoe::new(&Deck::builder(6), 1.0, CountingSystem::HiLo, 7).is_ok());
+}
+
+#[test]
+fn a_bet_ramp_needs_finite_limits_the_right_way_round() {
+    assert_eq!(ramp().validate(), Ok(()));
+    let bad = [
+        BetRamp { table_min: f64::NAN, ..ramp() },
+        BetRamp { min_units: f64::NAN, ..ramp() },
+        BetRamp { table_max: f64::INFINITY, ..ramp() },
+        BetRamp { table_min: 0.0, ..ramp() },
+        BetRamp { table_min: 200.0, ..ramp() },
+        BetRamp { min_units: 10.0, ..ramp() },
+    ];
+    for ramp in bad {
+        assert!(matches!(ramp.validate(), Err(GameError::InvalidRules(_))), "{:?}", ramp);
+    }
+}
+
+// This takes an entry out of the shared strategy table, so it is the only
+// test here that plays a round.
+#[test]
+fn a_situation_the_strategy_doesnt_cover_is_reported() {
+    BASIC_STRATEGY.lock().unwrap().remove("16,10");
+    let cards = parse_cards("T 7 T 6 T").unwrap();
+    let mut cards = Arc::new(Mutex::new(Shoe::stacked(cards, CountingSystem::HiLo)));
+    let mut game = Game::new(&mut cards).unwrap();
+    assert_eq!(game.start(), Err(GameError::MissingStrategy("16,10".to_string())));
+}
@@ -0,0 +1,156 @@
+use std::sync::{Arc, Mutex};
+
+use blackjack_rs::count::CountingSystem;
+use blackjack_rs::shoe::{parse_cards, Shoe};
+use blackjack_rs::{Agent, Card, Game, GameError, GameResult, Move, Phase};
+
+// Plays a round by basic strategy off a stacked shoe holding exactly the
+// given cards: the dealer's up and hole card, the player's two cards, then
+// whatever is drawn.
+fn play(stack: &str) -> Result<GameResult, GameError> {
+    let cards = parse_cards(stack).unwrap();
+    let mut cards = Arc::new(Mutex::new(Shoe::stacked(cards, CountingSystem::HiLo)));
+    let mut game = Game::new(&mut cards)?;
+    game.start()?;
+    Ok(GameResult::from(game))
+}
+
+// Deals a round off a stacked shoe without playing it.
+fn deal(stack: &str) -> Arc<Mutex<Shoe>> {
+    let cards = parse_cards(stack).unwrap();
+    Arc::new(Mutex::new(Shoe::stacked(cards, CountingSystem::HiLo)))
+}
+
+#[test]
+fn eights_are_split_against_an_ace_and_each_hand_played() {
+    let result = play("A 7 8 8 T 3 T T").unwrap();
+    let situations: Vec<&str> = result.decisions().iter().map(|d| d.situation.as_str()).collect();
+    let actions: Vec<&Move> = result.decisions().iter().map(|d| &d.action).collect();
+    assert_eq!(situations, ["8,8,11", "18,11", "11,11"]);
+    assert_eq!(actions, [&Move::Split, &Move::Stand, &Move::Double]);
+    assert_eq!(result.player_hands(), (&[Card::Eight, Card::Ten][..], &[Card::Eight, Card::Three, Card::Ten][..]));
+    assert_eq!(result.units_wagered(), 3.0);
+    // The dealer draws to eighteen's ten and busts, so both hands win, the
+    // doubled one two units.
+    assert_eq!(result.hand_winners(), [Some(Agent::Player), Some(Agent::Player)]);
+    assert_eq!(result.winner(), Some(Agent::Player));
+    assert_eq!(result.units(), 3.0);
+}
+
+#[test]
+fn split_hands_are_settled_against_the_dealers_final_total() {
+    // The dealer's seventeen draws to beat the best hand, nineteen, and
+    // busts, so both hands win.
+    let result = play("9 8 9 9 T 8 T").unwrap();
+    assert_eq!(result.hand_winners(), [Some(Agent::Player), Some(Agent::Player)]);
+    assert_eq!(result.winner(), Some(Agent::Player));
+    assert_eq!(result.units(), 2.0);
+}
+
+#[test]
+fn dealer_blackjack_under_an_ace_beats_twenty() {
+    let result = play("A K T T").unwrap();
+    assert_eq!(result.winner(), Some(Agent::Dealer));
+    assert!(!result.insured());
+    assert_eq!(result.units(), -1.0);
+}
+
+#[test]
+fn dealer_blackjack_under_a_ten_beats_a_standing_hand() {
+    let result = play("T A 9 9").unwrap();
+    let actions: Vec<&Move> = result.decisions().iter().map(|d| &d.action).collect();
+    assert_eq!(actions, [&Move::Stand]);
+    assert_eq!(result.winner(), Some(Agent::Dealer));
+    assert_eq!(result.units(), -1.0);
+}
+
+#[test]
+fn player_blackjack_pays_three_to_two() {
+    let result = play("9 7 A K").unwrap();
+    assert!(result.is_blackjack());
+    assert!(result.decisions().is_empty());
+    assert_eq!(result.units(), 1.5);
+}
+
+#[test]
+fn decisions_are_made_on_the_count_without_the_hole_card() {
+    // The up card ace is -1 and the eights 0, so the running count is -1
+    // with a quarter deck, the least the true count divides by, left.
+    let result = play("A T 8 8 6 T T").unwrap();
+    assert_eq!(result.decisions()[0].true_count, -4.0);
+}
+
+#[test]
+fn a_stack_that_runs_out_is_not_reshuffled() {
+    // Splitting the eights needs a card for each hand, but only one is left.
+    assert_eq!(play("A T 8 8 6").err(), Some(GameError::ExhaustedShoe));
+}
+
+#[test]
+fn a_split_the_chips_cant_cover_is_played_as_a_total() {
+    let cards = parse_cards("7 T 8 8 4 T").unwrap();
+    let mut cards = Arc::new(Mutex::new(Shoe::stacked(cards, CountingSystem::HiLo)));
+    let mut game = Game::new(&mut cards).unwrap().with_chips(15.0, 10.0);
+    game.step_until_decision().unwrap();
+    assert_eq!(game.legal_moves(), [Move::Hit, Move::Stand, Move::Surrender]);
+    game.start().unwrap();
+    let result = GameResult::from(game);
+    // Sixteen against a seven is hit rather than split.
+    let actions: Vec<&Move> = result.decisions().iter().map(|d| &d.action).collect();
+    assert_eq!(actions, [&Move::Hit, &Move::Stand]);
+    assert_eq!(result.units_wagered(), 1.0);
+    assert_eq!(result.units(), 1.0);
+}
+
+#[test]
+fn a_round_is_stepped_through_each_split_hand() {
+    let mut cards = deal("9 7 8 8 T 3 T T");
+    let mut game = Game::new(&mut cards).unwrap();
+    assert_eq!(game.phase(), Phase::Dealing);
+    assert_eq!(game.step().unwrap(), Phase::PlayerTurn(0));
+    assert!(game.legal_moves().contains(&Move::Split));
+    assert_eq!(game.apply(Move::Split).unwrap(), Phase::PlayerTurn(0));
+    assert_eq!(game.player_total(), 18);
+    // Neither hand can be split again or surrendered.
+    assert_eq!(game.legal_moves(), [Move::Hit, Move::Stand, Move::Double]);
+    assert_eq!(game.apply(Move::Stand).unwrap(), Phase::PlayerTurn(1));
+    assert_eq!(game.player_total(), 11);
+    assert_eq!(game.apply(Move::Double).unwrap(), Phase::DealerTurn);
+    assert_eq!(game.step_until_decision().unwrap(), Phase::Settled);
+    let result = GameResult::from(game);
+    assert_eq!(result.hand_winners(), [Some(Agent::Player), Some(Agent::Player)]);
+    assert_eq!(result.units(), 3.0);
+}
+
+#[test]
+fn a_move_that_isnt_legal_is_refused() {
+    let mut cards = deal("9 7 T 6 T");
+    let mut game = Game::new(&mut cards).unwrap();
+    assert_eq!(
+        game.apply(Move::Split),
+        Err(GameError::IllegalMove { action: Move::Split, phase: Phase::PlayerTurn(0) }),
+    );
+    assert_eq!(game.apply(Move::Stand).unwrap(), Phase::DealerTurn);
+    assert_eq!(
+        game.apply(Move::Hit),
+        Err(GameError::IllegalMove { action: Move::Hit, phase: Phase::DealerTurn }),
+    );
+}
+
+#[test]
+fn insurance_is_only_offered_against_an_ace() {
+    let mut cards = deal("T 7 T T");
+    let mut game = Game::new(&mut cards).unwrap();
+    assert_eq!(game.insure(true), Err(GameError::InsuranceNotOffered(Phase::PlayerTurn(0))));
+
+    let mut cards = deal("A 7 T T T");
+    let mut game = Game::new(&mut cards).unwrap();
+    assert_eq!(game.step().unwrap(), Phase::Insurance);
+    assert_eq!(game.insure(true).unwrap(), Phase::PlayerTurn(0));
+    assert_eq!(game.apply(Move::Stand).unwrap(), Phase::DealerTurn);
+    assert_eq!(game.step_until_decision().unwrap(), Phase::Settled);
+    // Twenty beats the dealer's bust, less the half unit of insurance lost.
+    let result = GameResult::from(game);
+    assert!(result.insured());
+    assert_eq!(result.units(), 0.5);
+}
@@ -0,0 +1,69 @@
+use std::sync::{Arc, Mutex};
+
+use blackjack_rs::count::CountingSystem;
+use blackjack_rs::shoe::{parse_cards, Shoe};
+use blackjack_rs::systems::System;
+use blackjack_rs::{Game, GameResult};
+
+// A round won or lost at the given bet: the player stands on twenty and the
+// dealer busts, or the player hits fifteen and busts.
+fn round(won: bool, bet: f64) -> GameResult {
+    let stack = if won { "T 7 T T T" } else { "T 7 T 5 T" };
+    let cards = parse_cards(stack).unwrap();
+    let mut cards = Arc::new(Mutex::new(Shoe::stacked(cards, CountingSystem::HiLo)));
+    let mut game = Game::new(&mut cards).unwrap();
+    game.start().unwrap();
+    GameResult::from(game).with_bet(bet, 0.0)
+}
+
+// The bets a system makes over a run of wins and losses, bet in units of the
+// given size.
+fn bets(system: System, unit: f64, outcomes: &[bool]) -> Vec<f64> {
+    let mut system = system.start();
+    let mut bet = system.next_bet(None, unit);
+    let mut bets = vec![bet];
+    for won in outcomes {
+        bet = system.next_bet(Some(&round(*won, bet * unit)), unit);
+        bets.push(bet);
+    }
+    bets
+}
+
+#[test]
+fn flat_always_bets_one_unit() {
+    assert_eq!(bets(System::Flat, 5.0, &[false, true, false]), [1.0, 1.0, 1.0, 1.0]);
+}
+
+#[test]
+fn martingale_doubles_after_a_loss_until_a_win() {
+    assert_eq!(bets(System::Martingale, 5.0, &[false, false, true]), [1.0, 2.0, 4.0, 1.0]);
+}
+
+#[test]
+fn paroli_doubles_after_a_win_for_three_wins() {
+    assert_eq!(bets(System::Paroli, 5.0, &[true, true, true, true, false]), [1.0, 2.0, 4.0, 1.0, 2.0, 1.0]);
+}
+
+#[test]
+fn fibonacci_steps_up_after_a_loss_and_back_two_after_a_win() {
+    assert_eq!(bets(System::Fibonacci, 5.0, &[false, false, false, true]), [1.0, 1.0, 2.0, 3.0, 1.0]);
+}
+
+#[test]
+fn dalembert_adds_a_unit_after_a_loss_and_takes_one_off_after_a_win() {
+    assert_eq!(bets(System::DAlembert, 5.0, &[false, false, true]), [1.0, 2.0, 3.0, 2.0]);
+}
+
+#[test]
+fn labouchere_adds_the_units_lost_to_the_line() {
+    // 1 2 3 4, then 1 2 3 4 5 after losing 5 units, then 2 3 4 and 3 after
+    // two wins, and a fresh line once that is won.
+    assert_eq!(bets(System::Labouchere, 5.0, &[false, true, true, true]), [5.0, 6.0, 6.0, 3.0, 5.0]);
+}
+
+#[test]
+fn oscars_grind_keeps_the_capped_bet_of_a_cycle() {
+    // The win after a loss would raise the bet to two units, but one is all
+    // the cycle needs, and it stays one unit after the next loss.
+    assert_eq!(bets(System::OscarsGrind, 5.0, &[false, true, false, false, true]), [1.0, 1.0, 1.0, 1.0, 1.0, 2.0]);
+}